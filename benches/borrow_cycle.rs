@@ -0,0 +1,39 @@
+//! Measures the throughput of borrowing and dropping borrows on several threads at once
+//!
+//! Compares the default ordering policy, whose increments are `Relaxed`, against
+//! `SeqCst` for every operation on the count. On x86 both compile to the same locked
+//! instructions, so the difference only shows on weakly ordered targets such as ARM:
+//!
+//! ```text
+//! cargo bench --bench borrow_cycle
+//! ```
+
+use atomic_lend_cell::atomic_counting::{AtomicLendCell, OrderingPolicy};
+use std::{hint::black_box, thread, time::{Duration, Instant}};
+
+const THREADS: usize = 4;
+const CYCLES: usize = 5_000_000;
+
+/// Times `CYCLES` borrow/drop cycles on each of `THREADS` threads sharing one cell
+fn cycle(policy: OrderingPolicy) -> Duration {
+    let cell = AtomicLendCell::new(7u64).with_ordering(policy);
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..CYCLES {
+                    drop(black_box(cell.borrow()));
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn main() {
+    for policy in [OrderingPolicy::AcquireRelease, OrderingPolicy::SeqCst] {
+        let elapsed = cycle(policy);
+        let per_cycle = elapsed / (THREADS * CYCLES) as u32;
+        println!("{policy:?}: {elapsed:?} ({per_cycle:?} per borrow/drop)");
+    }
+}
@@ -0,0 +1,55 @@
+//! Measures how borrowing on some threads slows down reading the data on others
+//!
+//! Run once with and once without the `padded` feature to compare:
+//!
+//! ```text
+//! cargo bench --bench contention
+//! cargo bench --bench contention --features padded
+//! ```
+
+use atomic_lend_cell::{atomic_counting, flag_based};
+use std::{hint::black_box, thread, time::{Duration, Instant}};
+
+const BORROWERS: usize = 2;
+const READERS: usize = 2;
+const READS: usize = 20_000_000;
+
+/// Times `READS` reads of the data on each reader while the borrowers keep borrowing
+fn contend(read: impl Fn() -> u64 + Sync, borrow: impl Fn() + Sync) -> Duration {
+    let done = std::sync::atomic::AtomicBool::new(false);
+    thread::scope(|s| {
+        for _ in 0..BORROWERS {
+            s.spawn(|| {
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    borrow();
+                }
+            });
+        }
+        let start = Instant::now();
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| s.spawn(|| {
+                for _ in 0..READS {
+                    black_box(read());
+                }
+            }))
+            .collect();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        let elapsed = start.elapsed();
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+        elapsed
+    })
+}
+
+fn main() {
+    let padded = if cfg!(feature = "padded") { "padded" } else { "unpadded" };
+
+    let counted = atomic_counting::AtomicLendCell::new(7u64);
+    let elapsed = contend(|| *black_box(&counted).as_ref(), || drop(black_box(counted.borrow())));
+    println!("ref-counting ({padded}): {:?}", elapsed);
+
+    let flagged = flag_based::AtomicLendCell::new(7u64);
+    let elapsed = contend(|| *black_box(&flagged).as_ref(), || drop(black_box(flagged.borrow())));
+    println!("flag-based ({padded}): {:?}", elapsed);
+}
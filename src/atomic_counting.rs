@@ -13,29 +13,114 @@
 //! Unlike standard Rust borrowing, `AtomicLendCell` allows multiple threads to access
 //! the same data simultaneously, while ensuring the original value outlives all borrows.
 
-use std::{ops::Deref, sync::atomic::{AtomicUsize, Ordering}};
+use crate::{BorrowError, BorrowGuard, ScopedBorrow, padded::Padded, traits::{BorrowHandle, LendCell}};
+use core::{any, cell::UnsafeCell, fmt, hash::{Hash, Hasher}, marker::PhantomData, mem::{self, ManuallyDrop}, ops::Deref, ptr::{self, NonNull}, sync::atomic::{AtomicUsize, Ordering}};
+#[cfg(feature = "std")]
+use crate::{owned::OwnedLendCell, scoped::LendScope};
+#[cfg(feature = "track-borrows")]
+use std::{backtrace::Backtrace, collections::BTreeMap, format, string::String, thread::ThreadId};
+#[cfg(feature = "std")]
+use std::{boxed::Box, collections::HashMap, hash::BuildHasher, ops::Range, vec::Vec, sync::{Arc, Mutex, PoisonError, atomic::AtomicBool}, thread::Thread, time::{Duration, Instant}};
 
 /// A container that allows thread-safe lending of its contained value
 ///
 /// `AtomicLendCell<T>` owns a value of type `T` and maintains an atomic reference count
 /// to track outstanding borrows. It ensures that the value isn't dropped while
 /// borrows exist, panicking if this invariant would be violated.
-pub struct AtomicLendCell<T> {
-    data: T,
-    refcount: AtomicUsize
+///
+/// The contained value may be unsized, so that e.g. a `Box<AtomicLendCell<[T; N]>>` can be
+/// coerced to a `Box<AtomicLendCell<[T]>>` and lend slice borrows.
+///
+/// Zero-sized values such as `()` are supported as well. Nothing is ever read through
+/// their borrows, but the borrows are still counted, so the cell checks them like any
+/// other. This is useful as a pure lifetime token, e.g. for handing out "permits".
+///
+/// # Moving the cell
+///
+/// Borrows point at the borrow count and the value inside the cell, so the cell must stay where
+/// it is while it is borrowed. The value never moves within the cell, so `as_ptr` returns
+/// the same address for as long as the cell stays in place, and borrows taken at different
+/// times all share it. Moving the cell while borrows exist, however, leaves them pointing at
+/// the old location, and the compiler can't catch this because borrows don't hold a
+/// lifetime. Pinning the cell to the stack with `pin!`, or keeping it in a `Box` (or an
+/// `Arc`), rules the move out:
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::pin::pin;
+///
+/// // The original binding is consumed, so the cell can no longer be moved
+/// let cell = pin!(AtomicLendCell::new(42));
+/// let borrow = cell.borrow();
+/// assert_eq!(borrow.as_ptr(), cell.as_ptr());
+///
+/// // Moving the box moves only the pointer, not the cell
+/// let boxed = Box::new(AtomicLendCell::new(42));
+/// let borrow = boxed.borrow();
+/// let moved = boxed;
+/// assert_eq!(borrow.as_ptr(), moved.as_ptr());
+/// drop(borrow);
+/// ```
+pub struct AtomicLendCell<T: ?Sized> {
+    refcount: Padded<RefCount>,
+    blocking: bool,
+    data: UnsafeCell<T>
 }
 
-impl<T> AtomicLendCell<T> {
+impl<T: ?Sized> AtomicLendCell<T> {
     /// Returns a reference to the contained value
     ///
     /// This method provides direct access to the value inside the cell without
     /// incrementing the reference counter.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the cell has been moved while borrowed.
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &T{
-        &self.data
+        self.refcount.check_not_moved();
+        unsafe {&*self.data.get()}
+    }
+
+    /// Returns a raw pointer to the contained value
+    ///
+    /// The pointer is the same one that borrows of this cell hold. Dereferencing it after
+    /// the cell has been dropped or moved is undefined behavior.
+    pub fn as_ptr(&self) -> *const T {
+        self.data.get()
+    }
+
+    /// Returns a mutable reference to the contained value if it is not borrowed
+    ///
+    /// Returns `None` while any `AtomicBorrowCell` issued by this cell is still alive,
+    /// since those borrows may be reading the value concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(42);
+    /// *cell.get_mut().unwrap() += 1;
+    ///
+    /// assert_eq!(*cell, 43);
+    /// ```
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.refcount.count.get_mut() & !SEALED == 0 {
+            Some(self.data.get_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pointer to the value handed to borrows
+    fn data_ptr(&self) -> NonNull<T> {
+        // `UnsafeCell::get` never returns null
+        unsafe {NonNull::new_unchecked(self.data.get())}
     }
 }
 
-impl<T> Deref for AtomicLendCell<T> {
+impl<T: ?Sized> Deref for AtomicLendCell<T> {
     type Target = T;
     /// Dereferences to the contained value
     ///
@@ -45,14 +130,22 @@ impl<T> Deref for AtomicLendCell<T> {
     }
 }
 
-impl<T> Drop for AtomicLendCell<T> {
+impl<T: ?Sized> Drop for AtomicLendCell<T> {
     /// Ensures no borrows exist when the cell is dropped
     ///
     /// If outstanding borrows exist when the cell is dropped, this will panic
-    /// to prevent use-after-free errors.
+    /// to prevent use-after-free errors, or abort the process if the
+    /// `abort-on-violation` feature is enabled.
+    /// Cells created with `new_blocking` instead wait for the outstanding borrows to
+    /// be dropped.
     fn drop(&mut self) {
-        if self.refcount.load(Ordering::Relaxed) > 0 {
-            panic!("An AtomicBorrowCell outlives the AtomicLendCell which issues it!");
+        if self.blocking {
+            self.wait_until_free();
+        }
+        // Synchronizes with the `Release` decrements of the dropped borrows, so that
+        // their reads of the data happen-before the data itself is dropped
+        if self.refcount.count.load(self.refcount.policy.acquire()) & !SEALED > 0 {
+            borrows_outlive_owner(&self.refcount, any::type_name::<T>());
         }
     }
 }
@@ -62,115 +155,3554 @@ impl<T> Drop for AtomicLendCell<T> {
 /// `AtomicBorrowCell<T>` holds a pointer to data in an `AtomicLendCell<T>` and
 /// automatically decrements the reference count when dropped. It can be safely
 /// cloned, sent between threads, and shared.
-pub struct AtomicBorrowCell<T> {
-    data_ptr: *const T,
-    refcount_ptr: *const AtomicUsize
+pub struct AtomicBorrowCell<T: ?Sized> {
+    data_ptr: NonNull<T>,
+    refcount_ptr: NonNull<RefCount>,
+    #[cfg(feature = "track-borrows")]
+    id: BorrowId
+}
+
+impl<T: ?Sized> AtomicBorrowCell<T> {
+    /// Creates a borrow for a count entry that has just been added
+    fn issue(data_ptr: NonNull<T>, refcount_ptr: NonNull<RefCount>) -> Self {
+        AtomicBorrowCell {
+            data_ptr,
+            refcount_ptr,
+            #[cfg(feature = "track-borrows")]
+            id: unsafe {refcount_ptr.as_ref()}.register()
+        }
+    }
+
+    /// Hands the count entry of this borrow over to a borrow of `data_ptr`
+    ///
+    /// Combinators call this only after their closure has returned, so that a panicking
+    /// closure drops `self` while unwinding and releases its count entry.
+    fn hand_over<U: ?Sized>(self, data_ptr: NonNull<U>) -> AtomicBorrowCell<U> {
+        let borrow = AtomicBorrowCell {
+            data_ptr,
+            refcount_ptr: self.refcount_ptr,
+            #[cfg(feature = "track-borrows")]
+            id: self.id
+        };
+        mem::forget(self);
+        borrow
+    }
 }
 
-impl<T> AtomicBorrowCell<T> {
+impl<T: ?Sized> AtomicBorrowCell<T> {
     /// Returns a reference to the borrowed value
     ///
     /// This method provides access to the value inside the original `AtomicLendCell`.
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &T{
-        unsafe {self.data_ptr.as_ref().unwrap()}
+        unsafe {self.data_ptr.as_ref()}
     }
-}
 
-impl<T> Deref for AtomicBorrowCell<T> {
-    type Target = T;
-    /// Dereferences to the borrowed value
+    /// Calls `f` with a reference to the borrowed value and returns its result
     ///
-    /// This provides convenient access to the borrowed value through the dereference operator (*).
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
+    /// Convenient for doing several operations on the value, e.g. atomic operations on
+    /// an `AtomicLendCell<AtomicUsize>`, without dereferencing the borrow each time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let cell = AtomicLendCell::new(AtomicUsize::new(0));
+    /// let borrow = cell.borrow();
+    /// let previous = borrow.with(|counter| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    ///     counter.fetch_add(1, Ordering::Relaxed)
+    /// });
+    /// assert_eq!(previous, 1);
+    /// ```
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(self.as_ref())
     }
-}
 
-impl<T> Drop for AtomicBorrowCell<T> {
-    /// Decrements the reference count when the borrow is dropped
-    fn drop(&mut self) {
-        unsafe {
-            self.refcount_ptr.as_ref().unwrap().fetch_sub(1, Ordering::Release);
-        }
+    /// Returns a guard that dereferences to the borrowed value
+    ///
+    /// The guard borrows this `AtomicBorrowCell`, so the borrow can't be dropped or
+    /// moved to another thread while the guard is in use, and the guard itself can't
+    /// be sent anywhere either. This gives back compile-time checking for code that
+    /// only uses the value locally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let borrow = cell.borrow();
+    /// let guard = borrow.as_guard();
+    ///
+    /// assert_eq!(guard.len() + guard[0], 4);
+    /// ```
+    pub fn as_guard(&self) -> BorrowGuard<'_, T> {
+        BorrowGuard::new(self.as_ref())
+    }
+
+    /// Converts the borrow into a `ReadView` that can only be read through
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(String::from("config"));
+    /// let view = cell.borrow().read_view();
+    ///
+    /// assert_eq!(view.len(), 6);
+    /// ```
+    pub fn read_view(self) -> ReadView<T> {
+        ReadView(self)
     }
-}
 
-// These trait implementations make `AtomicBorrowCell` safe to send between threads
-unsafe impl<T: Sync> Send for AtomicBorrowCell<T> {}
-unsafe impl<T: Sync> Sync for AtomicBorrowCell<T> {}
+    /// Clones the borrow unless the owner's borrow limit has been reached
+    ///
+    /// Only cells created with [`AtomicLendCell::with_limit`] have a limit, so for
+    /// other cells this always succeeds.
+    pub fn try_clone(&self) -> Option<Self> {
+        unsafe {self.refcount_ptr.as_ref()}.try_add(1).is_some().then(|| AtomicBorrowCell::issue(self.data_ptr, self.refcount_ptr))
+    }
 
-impl<T> AtomicLendCell<T> {
-    /// Creates a new `AtomicLendCell` containing the given value
+    /// Creates `n` clones of the borrow at once
+    ///
+    /// Like [`AtomicLendCell::borrow_many`], the reference count is bumped by `n` with a
+    /// single atomic operation, which is cheaper than cloning `n` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the owner was created with a limit that would be exceeded.
     ///
     /// # Examples
     ///
     /// ```
-    /// use atomic_lend_cell::AtomicLendCell;
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
     ///
     /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    /// let clones = borrow.clone_n(3);
+    /// assert_eq!(cell.borrow_count(), 4);
+    ///
+    /// drop(clones);
+    /// assert_eq!(cell.borrow_count(), 1);
     /// ```
-    pub fn new(data: T) -> Self {
-        Self {data, refcount: 0.into()}
+    #[cfg(feature = "std")]
+    pub fn clone_n(&self, n: usize) -> Vec<Self> {
+        unsafe {self.refcount_ptr.as_ref()}.add(n);
+        (0..n).map(|_| AtomicBorrowCell::issue(self.data_ptr, self.refcount_ptr)).collect()
     }
 
-    /// Creates a new `AtomicBorrowCell` for the contained value
+    /// Returns the id under which the `track-borrows` feature reports this borrow
     ///
-    /// This increments the internal reference count and returns a borrow that can
-    /// be sent to other threads. The borrow will automatically decrement the
-    /// reference count when dropped.
+    /// Projections such as `map` keep the id of the borrow they consume.
+    #[cfg(feature = "track-borrows")]
+    pub fn id(&self) -> BorrowId {
+        self.id
+    }
+
+    /// Returns the raw pointer to the borrowed value
+    ///
+    /// The pointer is returned without dereferencing it or checking that the owner is
+    /// still alive, e.g. for passing it across an FFI boundary.
+    ///
+    /// **Dereferencing the pointer after the owner has been dropped is undefined behavior.**
+    pub fn as_ptr(&self) -> *const T {
+        self.data_ptr.as_ptr()
+    }
+
+    /// Returns `true` if both borrows point at the same value
+    ///
+    /// Like `Arc::ptr_eq`, this compares addresses rather than values, and never reads
+    /// the values, so it may be called even after the owner has been dropped.
     ///
     /// # Examples
     ///
     /// ```
-    /// use atomic_lend_cell::AtomicLendCell;
+    /// use atomic_lend_cell::atomic_counting::{AtomicBorrowCell, AtomicLendCell};
     ///
     /// let cell = AtomicLendCell::new(42);
+    /// let other = AtomicLendCell::new(42);
     /// let borrow = cell.borrow();
     ///
+    /// assert!(AtomicBorrowCell::ptr_eq(&borrow, &borrow.clone()));
+    /// assert!(!AtomicBorrowCell::ptr_eq(&borrow, &other.borrow()));
+    /// ```
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        ptr::addr_eq(a.data_ptr.as_ptr(), b.data_ptr.as_ptr())
+    }
+
+    /// Returns `true` if both borrows were issued by the same cell
+    ///
+    /// Unlike `ptr_eq`, this compares the owner's reference count rather than the borrowed
+    /// values, so borrows projected onto different parts of one value share an owner.
+    /// It never reads the values or the owner, so it may be called even after the
+    /// owner has been dropped.
+    ///
+    /// Borrows created with `borrow_unchecked` are not tied to any owner's count, so
+    /// they all report the same owner as each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicBorrowCell, AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new((1, 2));
+    /// let other = AtomicLendCell::new((1, 2));
+    /// let first = cell.borrow().map(|pair| &pair.0);
+    /// let second = cell.borrow().map(|pair| &pair.1);
+    ///
+    /// assert!(AtomicBorrowCell::same_owner(&first, &second));
+    /// assert!(!AtomicBorrowCell::same_owner(&first, &other.borrow()));
+    /// ```
+    pub fn same_owner<U: ?Sized>(a: &Self, b: &AtomicBorrowCell<U>) -> bool {
+        ptr::eq(a.refcount_ptr.as_ptr(), b.refcount_ptr.as_ptr())
+    }
+
+    /// Projects the borrow onto a part of the borrowed value
+    ///
+    /// The returned borrow shares this borrow's reference count entry, so the count is
+    /// unchanged. The closure can only return references into its argument or to
+    /// `'static` data, so the projection stays valid for as long as the original borrow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new((1, String::from("hello")));
+    /// let borrow = cell.borrow().map(|pair| &pair.1);
+    ///
+    /// assert_eq!(*borrow, "hello");
+    /// ```
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(self, f: F) -> AtomicBorrowCell<U> {
+        let data_ptr = NonNull::from(f(self.as_ref()));
+        self.hand_over(data_ptr)
+    }
+
+    /// Projects the borrow onto a part of the borrowed value that may not exist
+    ///
+    /// If the closure returns `None`, the original borrow is handed back. Either way the
+    /// reference count is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(Ok::<i32, String>(42));
+    /// let borrow = cell.borrow().filter_map(|value| value.as_ref().err()).unwrap_err();
+    /// let borrow = borrow.filter_map(|value| value.as_ref().ok()).unwrap();
+    ///
     /// assert_eq!(*borrow, 42);
+    /// assert_eq!(cell.borrow_count(), 1);
     /// ```
-    pub fn borrow(&self) -> AtomicBorrowCell<T> {
-        self.refcount.fetch_add(1, Ordering::Acquire);
-        AtomicBorrowCell {data_ptr: (&self.data) as * const T, refcount_ptr: &self.refcount as * const AtomicUsize}
+    pub fn filter_map<U: ?Sized, F: FnOnce(&T) -> Option<&U>>(self, f: F) -> Result<AtomicBorrowCell<U>, Self> {
+        match f(self.as_ref()).map(NonNull::from) {
+            Some(data_ptr) => Ok(self.hand_over(data_ptr)),
+            None => Err(self)
+        }
+    }
+
+    /// Splits the borrow into borrows of two parts of the borrowed value
+    ///
+    /// Both borrows count against the same owner, so the count grows by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new((1, String::from("hello")));
+    /// let (number, text) = cell.borrow().split(|pair| (&pair.0, &pair.1));
+    ///
+    /// assert_eq!((*number, text.as_str()), (1, "hello"));
+    /// assert_eq!(cell.borrow_count(), 2);
+    /// ```
+    pub fn split<U: ?Sized, V: ?Sized, F: FnOnce(&T) -> (&U, &V)>(self, f: F) -> (AtomicBorrowCell<U>, AtomicBorrowCell<V>) {
+        let (first, second) = f(self.as_ref());
+        let (first, second) = (NonNull::from(first), NonNull::from(second));
+        let refcount_ptr = self.refcount_ptr;
+        unsafe {refcount_ptr.as_ref()}.add(1);
+        // The existing count entry is handed over to the first half
+        (self.hand_over(first), AtomicBorrowCell::issue(second, refcount_ptr))
+    }
+
+    /// Consumes the borrow, returning the pointers to the value and to its reference count
+    ///
+    /// The reference count is not decremented, so the owner stays borrowed until the
+    /// pointers are turned back into a borrow with [`from_raw`](Self::from_raw) and
+    /// that borrow is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicBorrowCell, AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let (data, rc) = cell.borrow().into_raw();
+    /// assert_eq!(cell.borrow_count(), 1);
+    ///
+    /// let borrow = unsafe { AtomicBorrowCell::from_raw(data, rc) };
+    /// assert_eq!(*borrow, 42);
+    /// drop(borrow);
+    /// assert_eq!(cell.borrow_count(), 0);
+    /// ```
+    pub fn into_raw(self) -> (*const T, *const AtomicUsize) {
+        let data_ptr = self.data_ptr;
+        let refcount_ptr = self.refcount_ptr;
+        // The raw pointers cannot carry the id, so `from_raw` registers a new one
+        #[cfg(feature = "track-borrows")]
+        unsafe {refcount_ptr.as_ref()}.unregister(self.id);
+        // The count entry is handed over to the caller
+        mem::forget(self);
+        (data_ptr.as_ptr().cast_const(), refcount_ptr.cast::<AtomicUsize>().as_ptr().cast_const())
+    }
+
+    /// Reconstructs a borrow from the pointers returned by [`into_raw`](Self::into_raw)
+    ///
+    /// The returned borrow decrements the reference count when dropped.
+    ///
+    /// # Safety
+    ///
+    /// The pointers must come from a single call to `into_raw`, and must not be passed
+    /// to `from_raw` more than once.
+    pub unsafe fn from_raw(data: *const T, rc: *const AtomicUsize) -> Self {
+        // Neither pointer can be null, since they come from `into_raw`
+        unsafe {
+            AtomicBorrowCell::issue(NonNull::new_unchecked(data.cast_mut()), NonNull::new_unchecked(rc.cast_mut()).cast())
+        }
     }
 }
 
-impl<'a, T> AtomicLendCell<&'a T> {
-    /// Creates a new `AtomicBorrowCell` that borrows the referenced value directly
+impl<T: ?Sized> Deref for AtomicBorrowCell<T> {
+    type Target = T;
+    /// Dereferences to the borrowed value
     ///
-    /// This is useful when the `AtomicLendCell` contains a reference, and you want to
-    /// borrow the underlying value rather than the reference itself.
-    pub fn borrow_deref(&'a self) -> AtomicBorrowCell<T> {
-        self.refcount.fetch_add(1, Ordering::Acquire);
-        AtomicBorrowCell {data_ptr: self.data as * const T, refcount_ptr: &self.refcount as * const AtomicUsize}
+    /// This provides convenient access to the borrowed value through the dereference operator (*).
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
     }
 }
 
-impl<T> Clone for AtomicBorrowCell<T> {
-    /// Creates a new `AtomicBorrowCell` that borrows the same value
+impl<T: ?Sized> Drop for AtomicBorrowCell<T> {
+    /// Decrements the reference count when the borrow is dropped
+    fn drop(&mut self) {
+        unsafe {
+            #[cfg(feature = "track-borrows")]
+            self.refcount_ptr.as_ref().unregister(self.id);
+            self.refcount_ptr.as_ref().release();
+        }
+    }
+}
+
+// The value is only replaced through `&mut self`, so sharing the cell only
+// requires sharing the value
+unsafe impl<T: ?Sized + Sync> Sync for AtomicLendCell<T> {}
+
+// These trait implementations make `AtomicBorrowCell` safe to send between threads,
+// unless the `no-send` feature keeps borrows on the thread that created them
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Send for AtomicBorrowCell<T> {}
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Sync for AtomicBorrowCell<T> {}
+
+/// A counted borrow that also borrows its owner for the lifetime `'a`
+///
+/// Created by [`AtomicLendCell::borrow_guarded`]. It is counted like an
+/// `AtomicBorrowCell`, but as long as it is alive the borrow checker rejects moving
+/// or dropping the owner, much like `std::cell::Ref` does for a `RefCell`.
+pub struct GuardedBorrow<'a, T: ?Sized> {
+    borrow: AtomicBorrowCell<T>,
+    _owner: PhantomData<&'a AtomicLendCell<T>>
+}
+
+impl<T: ?Sized> Deref for GuardedBorrow<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+impl<T: ?Sized> Clone for GuardedBorrow<'_, T> {
+    /// Creates a new guard of the same owner, incrementing the reference count
+    fn clone(&self) -> Self {
+        GuardedBorrow {borrow: self.borrow.clone(), _owner: PhantomData}
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for GuardedBorrow<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GuardedBorrow").field(&&**self).finish()
+    }
+}
+
+/// A borrow of a value that is lent to a single borrower at a time
+///
+/// Created by [`AtomicLendCell::borrow_exclusive`]. It mutably borrows the owner for
+/// `'a`, so only the thread holding it reads the value, and like a `&mut T` it can be
+/// sent to another thread as long as `T` is `Send`.
+pub struct ExclusiveBorrow<'a, T: ?Sized> {
+    data: &'a mut T
+}
+
+impl<T: ?Sized> ExclusiveBorrow<'_, T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<T: ?Sized> Deref for ExclusiveBorrow<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ExclusiveBorrow<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExclusiveBorrow")
+            .field("data", &self.as_ref())
+            .finish()
+    }
+}
+
+/// A borrow that has to be handed back explicitly
+///
+/// Created by [`AtomicLendCell::lease`]. It behaves like an [`AtomicBorrowCell`], but
+/// is expected to be given back with [`return_to_owner`](Self::return_to_owner).
+/// Dropping a lease that was never returned is treated as a leak and panics in debug
+/// builds; in release builds it is returned silently.
+pub struct Lease<T: ?Sized> {
+    borrow: AtomicBorrowCell<T>,
+    returned: bool
+}
+
+impl<T: ?Sized> Lease<T> {
+    /// Returns a reference to the leased value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        self.borrow.as_ref()
+    }
+
+    /// Hands the lease back to the owner, releasing its borrow
+    pub fn return_to_owner(mut self) {
+        self.returned = true;
+    }
+}
+
+impl<T: ?Sized> Deref for Lease<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Drop for Lease<T> {
+    /// Checks that the lease was returned; the borrow itself is released afterwards
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return;
+        }
+        debug_assert!(self.returned, "Lease dropped without being returned to its owner");
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Lease<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lease")
+            .field("data", &self.as_ref())
+            .finish()
+    }
+}
+
+/// A reserved borrow that can't be read until it is claimed
+///
+/// Created by [`AtomicLendCell::reserve`], which adds its entry to the reference count
+/// right away. [`claim`](Self::claim) then turns it into an [`AtomicBorrowCell`] without
+/// touching the count again, so the atomic operation can be paid for before the borrow
+/// is handed off, e.g. to another thread. Dropping an unclaimed ticket releases its
+/// entry like a borrow would.
+pub struct BorrowTicket<T: ?Sized> {
+    borrow: AtomicBorrowCell<T>
+}
+
+impl<T: ?Sized> BorrowTicket<T> {
+    /// Turns the ticket into a borrow, taking over its count entry
+    pub fn claim(self) -> AtomicBorrowCell<T> {
+        self.borrow
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BorrowTicket<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowTicket").finish_non_exhaustive()
+    }
+}
+
+/// A borrow that is a single pointer wide
+///
+/// Created by [`AtomicLendCell::borrow_compact`]. Instead of separate pointers to the
+/// value and to the reference count, it points at the whole cell and finds both at
+/// their fixed offsets, which halves its size for sized values. This pays off when many
+/// borrows are stored. In exchange, it always borrows the whole value: projecting it
+/// with `map` requires converting it into an `AtomicBorrowCell` first.
+///
+/// With the `track-borrows` feature, it additionally stores its id.
+pub struct CompactBorrowCell<T: ?Sized> {
+    cell: NonNull<AtomicLendCell<T>>,
+    #[cfg(feature = "track-borrows")]
+    id: BorrowId
+}
+
+impl<T: ?Sized> CompactBorrowCell<T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        unsafe {&*self.cell.as_ref().data.get()}
+    }
+
+    fn refcount(&self) -> &RefCount {
+        unsafe {&self.cell.as_ref().refcount}
+    }
+}
+
+impl<T: ?Sized> Deref for CompactBorrowCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Clone for CompactBorrowCell<T> {
+    /// Creates a new `CompactBorrowCell` that borrows the same value
     ///
-    /// This increments the reference count in the original `AtomicLendCell`.
+    /// # Panics
+    ///
+    /// Panics if the cell was created with a limit that has been reached.
     fn clone(&self) -> Self {
-        let count = unsafe {self.refcount_ptr.as_ref()}.unwrap();
-        count.fetch_add(1, Ordering::SeqCst);
-        AtomicBorrowCell {data_ptr: self.data_ptr, refcount_ptr: self.refcount_ptr}
+        self.refcount().add(1);
+        CompactBorrowCell {
+            cell: self.cell,
+            #[cfg(feature = "track-borrows")]
+            id: self.refcount().register()
+        }
     }
 }
 
-#[test]
-/// Tests that borrowing works across threads
-fn test_lambda_borrow(){
-    let x = AtomicLendCell::new(4);
-    let xr = x.borrow();
-    let t1 = std::thread::spawn(move ||{
-        let y = xr.as_ref();
-        println!("{:?}", y);
+impl<T: ?Sized> Drop for CompactBorrowCell<T> {
+    /// Decrements the reference count when the borrow is dropped
+    fn drop(&mut self) {
+        #[cfg(feature = "track-borrows")]
+        self.refcount().unregister(self.id);
+        self.refcount().release();
+    }
+}
+
+/// Expands the borrow into a regular `AtomicBorrowCell`, e.g. to project it with `map`
+///
+/// The count entry is handed over, so the reference count is unchanged.
+impl<T: ?Sized> From<CompactBorrowCell<T>> for AtomicBorrowCell<T> {
+    fn from(borrow: CompactBorrowCell<T>) -> Self {
+        let borrow = ManuallyDrop::new(borrow);
+        let cell = unsafe {borrow.cell.as_ref()};
+        AtomicBorrowCell {
+            data_ptr: cell.data_ptr(),
+            refcount_ptr: NonNull::from(&*cell.refcount),
+            #[cfg(feature = "track-borrows")]
+            id: borrow.id
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for CompactBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactBorrowCell")
+            .field("data", &self.as_ref())
+            .finish()
+    }
+}
+
+// Like `AtomicBorrowCell`, the borrow only hands out shared references to the value
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Send for CompactBorrowCell<T> {}
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Sync for CompactBorrowCell<T> {}
+
+impl<T> AtomicLendCell<T> {
+    /// Creates a new `AtomicLendCell` containing the given value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// ```
+    ///
+    /// This is a `const fn`, so cells can be placed in statics:
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// static CONFIG: AtomicLendCell<u32> = AtomicLendCell::new(7);
+    ///
+    /// assert_eq!(*CONFIG.borrow(), 7);
+    /// ```
+    pub const fn new(data: T) -> Self {
+        Self {refcount: Padded::new(RefCount::new()), blocking: false, data: UnsafeCell::new(data)}
+    }
+
+    /// Creates a new `AtomicLendCell` whose drop waits for outstanding borrows
+    ///
+    /// Instead of panicking when borrows are still alive, dropping this cell blocks the
+    /// dropping thread until all of them have been dropped. This suits shutdown paths
+    /// where workers are about to release their borrows, but will hang forever if a
+    /// borrow is leaked.
+    ///
+    /// Borrows point into the cell itself, so the cell has to be dropped in place, for
+    /// example by going out of scope. Moving it into `drop` leaves the borrows
+    /// decrementing a stale counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let handle;
+    /// {
+    ///     let cell = AtomicLendCell::new_blocking(42);
+    ///     let borrow = cell.borrow();
+    ///     handle = std::thread::spawn(move || assert_eq!(*borrow, 42));
+    ///     // Blocks here until the thread has dropped its borrow
+    /// }
+    /// handle.join().unwrap();
+    /// # }
+    /// ```
+    pub const fn new_blocking(data: T) -> Self {
+        Self {refcount: Padded::new(RefCount::new()), blocking: true, data: UnsafeCell::new(data)}
+    }
+
+    /// Creates a new `AtomicLendCell` that lends its value at most `max` times at once
+    ///
+    /// This protects values that can only sustain a limited number of concurrent
+    /// readers. Once `max` borrows are outstanding, `try_borrow` fails with
+    /// `BorrowError::LimitExceeded` and [`AtomicBorrowCell::try_clone`] returns `None`,
+    /// while `borrow` and `clone` panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::{BorrowError, atomic_counting::AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::with_limit(42, 2);
+    /// let first = cell.borrow();
+    /// let second = first.clone();
+    /// assert_eq!(cell.try_borrow().unwrap_err(), BorrowError::LimitExceeded);
+    ///
+    /// drop(second);
+    /// assert!(cell.try_borrow().is_ok());
+    /// ```
+    pub const fn with_limit(data: T, max: usize) -> Self {
+        let mut refcount = RefCount::new();
+        refcount.limit = max;
+        Self {refcount: Padded::new(refcount), blocking: false, data: UnsafeCell::new(data)}
+    }
+
+    /// Creates a new `AtomicLendCell` that records the most borrows it had at once
+    ///
+    /// The peak is read with [`peak_borrows`](AtomicLendCell::peak_borrows), e.g. to
+    /// find out the worst-case fan-out a shared resource has to sustain. Keeping it up
+    /// to date costs an extra atomic operation per borrow, so other cells don't track it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::with_high_water(42);
+    /// let borrows = [cell.borrow(), cell.borrow()];
+    /// drop(borrows);
+    ///
+    /// assert_eq!(cell.peak_borrows(), 2);
+    /// ```
+    pub const fn with_high_water(data: T) -> Self {
+        let mut refcount = RefCount::new();
+        refcount.track_peak = true;
+        Self {refcount: Padded::new(refcount), blocking: false, data: UnsafeCell::new(data)}
+    }
+
+    /// Creates a heap-backed cell whose value is freed by the last remaining handle
+    ///
+    /// Unlike the cells returned by `new`, the owner may be dropped before its borrows:
+    /// the value then stays alive until the last borrow is dropped, and the borrows
+    /// are marked as poisoned. This costs a heap allocation, so it's a separate type
+    /// rather than a mode of `AtomicLendCell`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new_owned(vec![1, 2, 3]);
+    /// let borrow = cell.borrow();
+    /// drop(cell);
+    ///
+    /// assert_eq!(*borrow, [1, 2, 3]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn new_owned(data: T) -> OwnedLendCell<T> {
+        OwnedLendCell::new(data)
+    }
+
+    /// Sets the memory orderings used for the reference count
+    ///
+    /// Has to be called before the cell is borrowed, since it moves the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicLendCell, OrderingPolicy};
+    ///
+    /// let cell = AtomicLendCell::new(42).with_ordering(OrderingPolicy::SeqCst);
+    /// assert_eq!(*cell.borrow(), 42);
+    /// ```
+    pub fn with_ordering(mut self, policy: OrderingPolicy) -> Self {
+        self.refcount.policy = policy;
+        self
+    }
+
+    /// Consumes the cell and returns the contained value
+    ///
+    /// # Panics
+    ///
+    /// Panics if borrows are still outstanding. Moving the cell into this method already
+    /// invalidates their pointers, so there is no cell left that could be handed back.
+    /// In that case the contained value is leaked rather than dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(String::from("hello"));
+    /// let borrow = cell.borrow();
+    /// assert_eq!(*borrow, "hello");
+    /// drop(borrow);
+    ///
+    /// assert_eq!(cell.into_inner(), "hello");
+    /// ```
+    pub fn into_inner(self) -> T {
+        // Suppress the destructor so the data is moved out exactly once
+        let mut this = ManuallyDrop::new(self);
+        if this.is_borrowed() {
+            borrows_outlive_owner(&this.refcount, any::type_name::<T>());
+        }
+        let data = unsafe {ptr::read(&this.data)}.into_inner();
+        unsafe {ptr::drop_in_place(&mut this.refcount)};
+        data
+    }
+
+    /// Consumes the cell and returns a new cell containing the transformed value
+    ///
+    /// The value is moved out as with `into_inner` and passed to `f`. The new cell starts
+    /// with no borrows and the default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if borrows are still outstanding, like `into_inner`, without calling `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(7u32);
+    /// let cell = cell.map_owner(|n| format!("#{n}"));
+    ///
+    /// assert_eq!(*cell.borrow(), "#7");
+    /// ```
+    pub fn map_owner<U>(self, f: impl FnOnce(T) -> U) -> AtomicLendCell<U> {
+        AtomicLendCell::new(f(self.into_inner()))
+    }
+
+    /// Replaces the contained value, returning the old one
+    ///
+    /// The value stays at the same address, so borrows issued later read the new value.
+    /// Taking `&mut self` rules out references obtained through `as_ref`, `Deref` or a
+    /// scope, while borrows, which don't hold a lifetime, are checked at runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if borrows are outstanding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(1);
+    /// assert_eq!(cell.replace(2), 1);
+    ///
+    /// assert_eq!(*cell.borrow(), 2);
+    /// ```
+    pub fn replace(&mut self, value: T) -> T {
+        match self.get_mut() {
+            Some(data) => mem::replace(data, value),
+            None => panic!("Cannot replace the value of an AtomicLendCell with outstanding borrows!")
+        }
+    }
+
+    /// Swaps the contained values of two cells
+    ///
+    /// Like [`replace`](Self::replace), this takes both cells by `&mut` and checks
+    /// their borrows at runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either cell has outstanding borrows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let mut front = AtomicLendCell::new(1);
+    /// let mut back = AtomicLendCell::new(2);
+    /// front.swap(&mut back);
+    ///
+    /// assert_eq!((*front, *back), (2, 1));
+    /// ```
+    pub fn swap(&mut self, other: &mut Self) {
+        match (self.get_mut(), other.get_mut()) {
+            (Some(data), Some(other_data)) => mem::swap(data, other_data),
+            _ => panic!("Cannot swap the value of an AtomicLendCell with outstanding borrows!")
+        }
+    }
+
+    /// Takes the contained value, leaving `T::default()` in its place
+    ///
+    /// This is [`replace`](Self::replace) with the default value, so it likewise takes
+    /// `&mut self` and checks for borrows at runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if borrows are outstanding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// assert_eq!(cell.take(), vec![1, 2, 3]);
+    ///
+    /// assert!(cell.borrow().is_empty());
+    /// ```
+    pub fn take(&mut self) -> T
+    where
+        T: Default
+    {
+        self.replace(T::default())
+    }
+
+    /// Waits up to `timeout` for all borrows to be dropped and returns the contained value
+    ///
+    /// If borrows are still outstanding when the timeout expires, the cell is handed
+    /// back so the caller can decide how to proceed. The cell is taken as a `Box` so
+    /// that it stays at a stable address while the borrows are being waited for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    /// use std::time::Duration;
+    ///
+    /// let cell = Box::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow();
+    ///
+    /// let cell = cell.try_reclaim(Duration::from_millis(10)).unwrap_err();
+    /// drop(borrow);
+    ///
+    /// assert_eq!(cell.try_reclaim(Duration::from_secs(5)).ok(), Some(42));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_reclaim(self: Box<Self>, timeout: Duration) -> Result<T, Box<Self>> {
+        let deadline = Instant::now() + timeout;
+        if self.refcount.wait(|| Instant::now() >= deadline) {
+            Ok((*self).into_inner())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Drops the cell if it isn't borrowed, or hands it back along with the borrow count
+    ///
+    /// Unlike dropping the cell, this never panics or blocks, which leaves it to the
+    /// caller to decide what to do about the outstanding borrows, e.g. retrying later
+    /// during a graceful shutdown. As with [`try_reclaim`](Self::try_reclaim), the
+    /// cell is taken as a `Box` so that it stays at a stable address while borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = Box::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow();
+    ///
+    /// let (cell, outstanding) = cell.try_drop().unwrap_err();
+    /// assert_eq!(outstanding, 1);
+    ///
+    /// drop(borrow);
+    /// assert!(cell.try_drop().is_ok());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_drop(self: Box<Self>) -> Result<(), (Box<Self>, usize)> {
+        match self.borrow_count() {
+            0 => Ok(()),
+            outstanding => Err((self, outstanding))
+        }
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<T> {
+    /// Creates a new `AtomicBorrowCell` for the contained value
+    ///
+    /// This increments the internal reference count and returns a borrow that can
+    /// be sent to other threads if `T` is `Sync`. The borrow will automatically
+    /// decrement the reference count when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell has been [sealed](AtomicLendCell::seal) or its borrow limit
+    /// has been reached. Use [`try_borrow`](AtomicLendCell::try_borrow) to handle that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn borrow(&self) -> AtomicBorrowCell<T> {
+        self.refcount.lend(1);
+        AtomicBorrowCell::issue(self.data_ptr(), NonNull::from(&*self.refcount))
+    }
+
+    /// Creates a new `AtomicBorrowCell` that is known to be sendable
+    ///
+    /// This is `borrow` with a `T: Sync` bound, for borrows that are meant for other
+    /// threads. A borrow of a value that isn't `Sync` can't be sent anyway, but with
+    /// `borrow` that is only reported where the borrow is sent, while this reports it
+    /// where the borrow is created. With the `no-send` feature no borrow is sendable,
+    /// whatever the bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `borrow`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let borrow = cell.borrow_sync();
+    /// let sum = std::thread::scope(|s| s.spawn(move || borrow.iter().sum::<i32>()).join().unwrap());
+    ///
+    /// assert_eq!(sum, 6);
+    /// # }
+    /// ```
+    pub fn borrow_sync(&self) -> AtomicBorrowCell<T>
+    where
+        T: Sync
+    {
+        self.borrow()
+    }
+
+    /// Creates a new counted borrow that keeps the cell borrowed while it lives
+    ///
+    /// Unlike `borrow`, the returned guard holds on to `&self`, so the compiler
+    /// guarantees that it doesn't outlive the cell and that the cell isn't moved in the
+    /// meantime. Use this when the borrow doesn't have to leave the current scope, or
+    /// only goes to scoped threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `borrow`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let guard = cell.borrow_guarded();
+    ///
+    /// assert_eq!(guard.len(), 3);
+    /// assert_eq!(cell.borrow_count(), 1);
+    /// ```
+    pub fn borrow_guarded(&self) -> GuardedBorrow<'_, T> {
+        GuardedBorrow {borrow: self.borrow(), _owner: PhantomData}
+    }
+
+    /// Creates an `AtomicBorrowCell` that is not counted by the cell
+    ///
+    /// Neither creating nor dropping the borrow, or any of its clones, touches the
+    /// reference count, so it costs no atomic operations. In exchange, the cell can
+    /// no longer detect the borrow outliving it.
+    ///
+    /// # Safety
+    ///
+    /// The borrow and all borrows derived from it must be dropped before the cell is
+    /// dropped, moved, or has its value replaced, and must not be used while the value
+    /// is lent through an `ExclusiveBorrow`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let borrow = unsafe { cell.borrow_unchecked() };
+    ///
+    /// assert_eq!(borrow.len(), 3);
+    /// assert_eq!(cell.borrow_count(), 0);
+    /// ```
+    pub unsafe fn borrow_unchecked(&self) -> AtomicBorrowCell<T> {
+        AtomicBorrowCell::issue(self.data_ptr(), NonNull::from(&UNCOUNTED))
+    }
+
+    /// Lends the contained value to a single borrower
+    ///
+    /// The returned `ExclusiveBorrow` cannot be cloned, so it is `Send` whenever `T` is,
+    /// which allows lending values that are not `Sync` to another thread. It borrows the
+    /// cell mutably, so the owner can't access the value or lend it again until it is
+    /// dropped, and no reference taken from the owner earlier can still be in use.
+    ///
+    /// Returns `None` if the cell is borrowed or sealed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    /// use std::cell::Cell;
+    ///
+    /// let mut cell = AtomicLendCell::new(Cell::new(1));
+    /// let borrow = cell.borrow_exclusive().unwrap();
+    /// std::thread::scope(|s| s.spawn(move || borrow.set(2)).join().unwrap());
+    ///
+    /// assert_eq!(cell.get(), 2);
+    /// ```
+    pub fn borrow_exclusive(&mut self) -> Option<ExclusiveBorrow<'_, T>> {
+        if *self.refcount.count.get_mut() != 0 {
+            return None;
+        }
+        if self.refcount.track_peak {
+            self.refcount.peak.fetch_max(1, Ordering::Relaxed);
+        }
+        Some(ExclusiveBorrow {data: self.data.get_mut()})
+    }
+
+    /// Leases the contained value
+    ///
+    /// A lease is a borrow that must be given back with [`Lease::return_to_owner`]
+    /// instead of being dropped, which helps catch leaked borrows in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let lease = cell.lease();
+    /// assert_eq!(*lease, 42);
+    /// assert_eq!(cell.borrow_count(), 1);
+    ///
+    /// lease.return_to_owner();
+    /// assert_eq!(cell.borrow_count(), 0);
+    /// ```
+    pub fn lease(&self) -> Lease<T> {
+        Lease {borrow: self.borrow(), returned: false}
+    }
+
+    /// Reserves a borrow, returning a `BorrowTicket` to claim it with later
+    ///
+    /// The reference count is incremented here, so claiming the ticket needs no further
+    /// atomic operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`borrow`](AtomicLendCell::borrow).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let ticket = cell.reserve();
+    /// assert_eq!(cell.borrow_count(), 1);
+    ///
+    /// let borrow = ticket.claim();
+    /// assert_eq!(cell.borrow_count(), 1);
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn reserve(&self) -> BorrowTicket<T> {
+        BorrowTicket {borrow: self.borrow()}
+    }
+
+    /// Creates a new `CompactBorrowCell`, a borrow that is a single pointer wide
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`borrow`](AtomicLendCell::borrow).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicBorrowCell, AtomicLendCell, CompactBorrowCell};
+    /// use std::mem::size_of;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow_compact();
+    /// assert_eq!(*borrow, 42);
+    /// assert!(size_of::<CompactBorrowCell<i32>>() < size_of::<AtomicBorrowCell<i32>>());
+    /// ```
+    pub fn borrow_compact(&self) -> CompactBorrowCell<T> {
+        self.refcount.lend(1);
+        CompactBorrowCell {
+            cell: NonNull::from(self),
+            #[cfg(feature = "track-borrows")]
+            id: self.refcount.register()
+        }
+    }
+
+    /// Creates `n` borrows of the contained value at once
+    ///
+    /// The reference count is bumped by `n` with a single atomic operation, which is
+    /// cheaper than calling [`borrow`](Self::borrow) `n` times when fanning out to many
+    /// threads. Each borrow still decrements the count on its own when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrows = cell.borrow_many(4);
+    /// assert_eq!(cell.borrow_count(), 4);
+    ///
+    /// drop(borrows);
+    /// assert_eq!(cell.borrow_count(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn borrow_many(&self, n: usize) -> Vec<AtomicBorrowCell<T>> {
+        self.refcount.lend(n);
+        let data_ptr = self.data_ptr();
+        let refcount_ptr = NonNull::from(&*self.refcount);
+        (0..n).map(|_| AtomicBorrowCell::issue(data_ptr, refcount_ptr)).collect()
+    }
+
+    /// Creates a new `AtomicBorrowCell` if the cell can still lend its value
+    ///
+    /// The reference-counting cell cannot be observed after it is dropped, so this
+    /// only fails with `BorrowError::Sealed` if the cell has been
+    /// [sealed](AtomicLendCell::seal), or with `BorrowError::LimitExceeded` if it was
+    /// created with [`with_limit`](AtomicLendCell::with_limit) and the limit has been
+    /// reached. It also allows code to be written generically over both implementations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.try_borrow().unwrap();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn try_borrow(&self) -> Result<AtomicBorrowCell<T>, BorrowError> {
+        self.refcount.try_lend(1)?;
+        Ok(AtomicBorrowCell::issue(self.data_ptr(), NonNull::from(&*self.refcount)))
+    }
+
+    /// Returns the number of outstanding `AtomicBorrowCell`s
+    ///
+    /// The count includes clones of borrows. Since other threads may create or drop
+    /// borrows concurrently, the returned value is only a snapshot.
+    pub fn borrow_count(&self) -> usize {
+        self.refcount.count.load(self.refcount.policy.acquire()) & !SEALED
+    }
+
+    /// Returns the largest number of borrows that were outstanding at the same time
+    ///
+    /// Only cells created with [`with_high_water`](AtomicLendCell::with_high_water)
+    /// track the peak; for other cells this always returns 0.
+    pub fn peak_borrows(&self) -> usize {
+        self.refcount.peak.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if any `AtomicBorrowCell` issued by this cell is still alive
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow_count() > 0
+    }
+
+    /// Stops the cell from issuing new borrows
+    ///
+    /// Afterwards `try_borrow` fails with `BorrowError::Sealed` and `borrow` panics, while the existing
+    /// borrows stay valid and can still be cloned. Together with
+    /// [`wait_until_free`](AtomicLendCell::wait_until_free) or
+    /// [`try_drop`](AtomicLendCell::try_drop), this allows draining a shared value:
+    /// stop handing it out, then wait for the last reader to finish.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::{BorrowError, atomic_counting::AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// cell.seal();
+    /// assert_eq!(cell.try_borrow().unwrap_err(), BorrowError::Sealed);
+    /// assert_eq!(*borrow, 42);
+    ///
+    /// drop(borrow);
+    /// assert_eq!(cell.borrow_count(), 0);
+    /// ```
+    pub fn seal(&self) {
+        // Sealing is ordered against concurrent borrows by the modification order of
+        // the count, so no synchronization is needed
+        self.refcount.count.fetch_or(SEALED, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the cell has been sealed against new borrows
+    pub fn is_sealed(&self) -> bool {
+        self.refcount.is_sealed()
+    }
+
+    /// Returns a snapshot of the cell's state for diagnostics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicLendCell, CellState};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(cell.state(), CellState::Alive { borrows: 1 });
+    /// ```
+    pub fn state(&self) -> CellState {
+        let borrows = self.borrow_count();
+        #[cfg(feature = "std")]
+        if self.refcount.waiting.load(Ordering::Acquire) {
+            return CellState::Waiting { borrows };
+        }
+        CellState::Alive { borrows }
+    }
+
+    /// Returns the ids of the outstanding borrows, oldest first
+    ///
+    /// Together with [`AtomicBorrowCell::id`], this helps to find leaked borrows. The
+    /// panic raised when the owner is dropped lists the same borrows along with a
+    /// backtrace of their creation, if backtraces are enabled through `RUST_BACKTRACE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(cell.outstanding(), [borrow.id()]);
+    /// ```
+    #[cfg(feature = "track-borrows")]
+    pub fn outstanding(&self) -> Vec<BorrowId> {
+        self.refcount.registry.lock().unwrap_or_else(PoisonError::into_inner).keys().copied().collect()
+    }
+
+    /// Lends the contained value to a closure as a `ScopedBorrow`
+    ///
+    /// The borrow is tied to the closure by its lifetime, so the compiler guarantees that
+    /// it does not outlive the cell and the reference count is left untouched. Use this when the value only needs
+    /// to be shared with scoped threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let doubled = cell.with_scope(|borrow| *borrow * 2);
+    ///
+    /// assert_eq!(doubled, 84);
+    /// ```
+    pub fn with_scope<F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(ScopedBorrow<'scope, T>) -> R
+    {
+        f(ScopedBorrow::new(self.as_ref()))
+    }
+
+    /// Blocks the current thread until no borrows are outstanding
+    ///
+    /// The thread is parked and woken up by the borrow that brings the count down to
+    /// zero, so no time is wasted spinning. Without `std`, this can only spin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// std::thread::scope(|s| {
+    ///     for _ in 0..4 {
+    ///         let borrow = cell.borrow();
+    ///         s.spawn(move || assert_eq!(*borrow, 42));
+    ///     }
+    ///     cell.wait_until_free();
+    ///     assert!(!cell.is_borrowed());
+    /// });
+    /// # }
+    /// ```
+    pub fn wait_until_free(&self) {
+        self.refcount.wait(|| false);
+    }
+
+    /// Runs `f` in a `std::thread::scope` that spawns threads with borrows of this cell
+    ///
+    /// This is the one-call form of the most common correct usage: every thread spawned
+    /// through the `LendScope` is joined before this returns, so no borrow outlives
+    /// the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let sums: Vec<i32> = cell.scope(|s| {
+    ///     let workers: Vec<_> = (0..3)
+    ///         .map(|i| s.spawn_with_borrow(move |borrow| borrow[i] * 10))
+    ///         .collect();
+    ///     workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    /// });
+    ///
+    /// assert_eq!(sums, [10, 20, 30]);
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn scope<'env, F, R>(&'env self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&LendScope<'scope, 'env, Self>) -> R
+    {
+        std::thread::scope(|s| f(&LendScope::new(s, self)))
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<&T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the referenced value directly
+    ///
+    /// This is useful when the `AtomicLendCell` contains a reference, and you want to
+    /// borrow the underlying value rather than the reference itself.
+    pub fn borrow_deref(&self) -> AtomicBorrowCell<T> {
+        self.refcount.lend(1);
+        AtomicBorrowCell::issue(NonNull::from(unsafe {*self.data.get()}), NonNull::from(&*self.refcount))
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<AtomicLendCell<T>> {
+    /// Creates a new `AtomicBorrowCell` of the value in the nested cell
+    ///
+    /// The borrow is issued by the outer cell, so it is this cell that has to outlive
+    /// it. The inner cell can't be dropped on its own while the outer one is borrowed,
+    /// so the borrow also keeps the nested value alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow_inner();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn borrow_inner(&self) -> AtomicBorrowCell<T> {
+        self.borrow().map(|inner| inner.as_ref())
+    }
+}
+
+impl<T: Clone> AtomicBorrowCell<T> {
+    /// Clones the borrowed value into an independent owned copy
+    ///
+    /// The copy is not tied to the owner, so it can be kept after the borrow and the
+    /// owner are gone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let snapshot = cell.borrow().to_owned();
+    /// drop(cell);
+    ///
+    /// assert_eq!(snapshot, [1, 2, 3]);
+    /// ```
+    pub fn to_owned(&self) -> T {
+        self.as_ref().clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S: BuildHasher> AtomicBorrowCell<HashMap<K, V, S>> {
+    /// Projects the borrow of a map onto the value stored under `key`
+    ///
+    /// The new borrow takes over the existing one. If the key is missing, the borrow is
+    /// released and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    /// use std::collections::HashMap;
+    ///
+    /// let cell = AtomicLendCell::new(HashMap::from([("alpha", 1), ("beta", 2)]));
+    ///
+    /// assert_eq!(cell.borrow().get("beta").as_deref(), Some(&2));
+    /// assert!(cell.borrow().get("gamma").is_none());
+    /// ```
+    pub fn get<Q: ?Sized + Hash + Eq>(self, key: &Q) -> Option<AtomicBorrowCell<V>>
+    where
+        K: core::borrow::Borrow<Q> + Hash + Eq
+    {
+        self.filter_map(|map| map.get(key)).ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> AtomicLendCell<Vec<T>> {
+    /// Creates a new `AtomicBorrowCell` of a range of the contained vector
+    ///
+    /// This is convenient for handing each worker a contiguous chunk of a shared buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the vector, or if it starts after it ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3, 4]);
+    /// let middle = cell.borrow_range(1..3);
+    ///
+    /// assert_eq!(*middle, [2, 3]);
+    /// ```
+    pub fn borrow_range(&self, range: Range<usize>) -> AtomicBorrowCell<[T]> {
+        let len = self.as_ref().len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "Range {range:?} is out of bounds for an AtomicLendCell<Vec<_>> of length {len}"
+        );
+        self.borrow().map(|data| &data[range])
+    }
+
+    /// Creates one `AtomicBorrowCell` per element of the contained vector
+    ///
+    /// The borrows are created lazily as the iterator is advanced, each adding one to
+    /// the owner's borrows, so elements can be handed to workers one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let elements: Vec<_> = cell.borrow_each().collect();
+    ///
+    /// assert_eq!(*elements[2], 3);
+    /// ```
+    pub fn borrow_each(&self) -> impl Iterator<Item = AtomicBorrowCell<T>> + '_ {
+        (0..self.as_ref().len()).map(move |i| self.borrow().map(|data| &data[i]))
+    }
+}
+
+/// A cell that lends a value stored on the heap
+///
+/// Created by [`AtomicLendCell::from_box`]. Only the box pointer is moved into the
+/// cell, so large values are lent without being copied, and the value stays at the same
+/// address for as long as the cell exists. Unlike `AtomicLendCell<Box<T>>`, this
+/// dereferences to `T` and hands out borrows of `T`.
+#[cfg(feature = "std")]
+pub struct BoxedLendCell<T: ?Sized>(AtomicLendCell<Box<T>>);
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> AtomicLendCell<T> {
+    /// Creates a cell that lends the boxed value without moving it off the heap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+    ///
+    /// let payload = vec![0u8; 1 << 20].into_boxed_slice();
+    /// let address = payload.as_ptr();
+    ///
+    /// let cell = AtomicLendCell::from_box(payload);
+    /// assert_eq!(cell.as_ptr(), address);
+    /// assert_eq!(cell.borrow()[0], 0);
+    /// ```
+    pub fn from_box(data: Box<T>) -> BoxedLendCell<T> {
+        BoxedLendCell(AtomicLendCell::new(data))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> BoxedLendCell<T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the boxed value
+    pub fn borrow(&self) -> AtomicBorrowCell<T> {
+        self.0.borrow().map(|data| &**data)
+    }
+
+    /// Consumes the cell, returning the box
+    pub fn into_box(self) -> Box<T> {
+        self.0.into_inner()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for BoxedLendCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for BoxedLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxedLendCell").field(&&**self).finish()
+    }
+}
+
+/// Borrowing from a cell that is shared through an `Arc`
+///
+/// The borrows hold a clone of the `Arc` next to the usual borrow, so the cell is kept
+/// alive for as long as they exist and can't be dropped out from under them.
+#[cfg(feature = "std")]
+pub trait ArcLendExt<T: ?Sized> {
+    /// Creates a new `ArcBorrow` that keeps the cell alive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::atomic_counting::{AtomicLendCell, ArcLendExt};
+    /// use std::sync::Arc;
+    ///
+    /// let cell = Arc::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow();
+    /// drop(cell);
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    fn borrow(&self) -> ArcBorrow<T>;
+}
+
+/// A borrow of a cell shared through an `Arc`, created by [`ArcLendExt::borrow`]
+///
+/// It dereferences to the borrowed value directly rather than through the `Arc`.
+#[cfg(feature = "std")]
+pub struct ArcBorrow<T: ?Sized> {
+    // Declared first so the borrow is released before the cell may be dropped
+    borrow: AtomicBorrowCell<T>,
+    owner: Arc<AtomicLendCell<T>>
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> ArcLendExt<T> for Arc<AtomicLendCell<T>> {
+    fn borrow(&self) -> ArcBorrow<T> {
+        ArcBorrow {borrow: AtomicLendCell::borrow(self), owner: Arc::clone(self)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> ArcBorrow<T> {
+    /// Returns the cell this borrow keeps alive
+    pub fn owner(this: &Self) -> &Arc<AtomicLendCell<T>> {
+        &this.owner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for ArcBorrow<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Clone for ArcBorrow<T> {
+    fn clone(&self) -> Self {
+        ArcBorrow {borrow: self.borrow.clone(), owner: Arc::clone(&self.owner)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ArcBorrow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArcBorrow").field(&&**self).finish()
+    }
+}
+
+/// Creates a cell containing the given value
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell: AtomicLendCell<_> = vec![1, 2, 3].into();
+/// assert_eq!(*cell.borrow(), [1, 2, 3]);
+/// ```
+impl<T> From<T> for AtomicLendCell<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+/// Creates a cell containing the default value of `T`
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// #[derive(Default)]
+/// struct Config {
+///     retries: AtomicLendCell<u32>
+/// }
+///
+/// assert_eq!(*Config::default().retries, 0);
+/// ```
+impl<T: Default> Default for AtomicLendCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for AtomicLendCell<T> {
+    /// Creates an independent cell holding a clone of the value
+    ///
+    /// The new cell starts without borrows, and keeps the blocking behavior, borrow
+    /// limit, peak tracking and ordering policy of the original.
+    fn clone(&self) -> Self {
+        let mut clone = Self::with_limit(self.as_ref().clone(), self.refcount.limit).with_ordering(self.refcount.policy);
+        clone.blocking = self.blocking;
+        clone.refcount.track_peak = self.refcount.track_peak;
+        clone
+    }
+}
+
+impl<T: ?Sized> Clone for AtomicBorrowCell<T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the same value
+    ///
+    /// This increments the reference count in the original `AtomicLendCell`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell was created with a limit that has been reached. Use
+    /// [`try_clone`](AtomicBorrowCell::try_clone) to handle that case.
+    fn clone(&self) -> Self {
+        unsafe {self.refcount_ptr.as_ref()}.add(1);
+        AtomicBorrowCell::issue(self.data_ptr, self.refcount_ptr)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AtomicLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicLendCell")
+            .field("data", &self.as_ref())
+            .field("refcount", &self.borrow_count())
+            .finish()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AtomicBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicBorrowCell")
+            .field("data", &self.as_ref())
+            .finish()
+    }
+}
+
+/// A borrow restricted to reading the borrowed value
+///
+/// Created by [`AtomicBorrowCell::read_view`]. It keeps the owner's count and liveness
+/// behavior of the borrow it wraps, but can't be cloned, projected or turned into raw
+/// pointers, which makes it suitable for handing to code that should only look at the
+/// value:
+///
+/// ```compile_fail,E0308
+/// use atomic_lend_cell::atomic_counting::{AtomicLendCell, ReadView};
+///
+/// let cell = AtomicLendCell::new(42);
+/// let view = cell.borrow().read_view();
+/// let copy: ReadView<i32> = view.clone();
+/// ```
+pub struct ReadView<T: ?Sized>(AtomicBorrowCell<T>);
+
+impl<T: ?Sized> ReadView<T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+impl<T: ?Sized> Deref for ReadView<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ReadView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadView").field(&self.0).finish()
+    }
+}
+
+/// Formats the address of the borrowed value
+///
+/// The value itself is never read, so this is fine even after the owner has been
+/// dropped, e.g. to correlate borrows in logs.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// println!("{:p}", borrow);
+///
+/// assert_eq!(format!("{:p}", borrow), format!("{:p}", borrow.as_ptr()));
+/// ```
+impl<T: ?Sized> fmt::Pointer for AtomicBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.data_ptr, f)
+    }
+}
+
+/// Formats the contained value
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// println!("{}", cell);
+/// assert_eq!(format!("{} {}", cell, cell.borrow()), "42 42");
+/// ```
+impl<T: ?Sized + fmt::Display> fmt::Display for AtomicLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_ref(), f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for AtomicBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_ref(), f)
+    }
+}
+
+/// Reports that an `AtomicBorrowCell` outlives the `AtomicLendCell` which issued it
+///
+/// Unwinding from here hands the surviving borrows a cell that is being torn down, so
+/// the `abort-on-violation` feature turns this into a deterministic process abort.
+///
+/// The message names the type of the value, and with the `track-borrows` feature also
+/// lists the outstanding borrows and where they were created.
+#[cold]
+fn borrows_outlive_owner(refcount: &RefCount, type_name: &str) -> ! {
+    #[cfg(feature = "track-borrows")]
+    let report = refcount.report();
+    #[cfg(not(feature = "track-borrows"))]
+    let report = {
+        let _ = refcount;
+        ""
+    };
+    #[cfg(feature = "abort-on-violation")]
+    {
+        std::eprintln!("An AtomicBorrowCell<{type_name}> outlives the AtomicLendCell which issues it!{report}");
+        std::process::abort();
+    }
+    #[cfg(not(feature = "abort-on-violation"))]
+    panic!("An AtomicBorrowCell<{type_name}> outlives the AtomicLendCell which issues it!{report}");
+}
+
+/// The reference count shared between a cell and its borrows
+///
+/// Besides the count itself, it records a thread waiting for the count to drop to zero,
+/// so that the borrow bringing it there can wake the thread up.
+///
+/// The count comes first, so that a pointer to it handed out by
+/// `AtomicBorrowCell::into_raw` can be cast back to the whole struct.
+#[repr(C)]
+struct RefCount {
+    count: AtomicUsize,
+    policy: OrderingPolicy,
+    limit: usize,
+    track_peak: bool,
+    peak: AtomicUsize,
+    #[cfg(feature = "std")]
+    waiting: AtomicBool,
+    #[cfg(feature = "std")]
+    waiter: Mutex<Option<Thread>>,
+    #[cfg(feature = "track-borrows")]
+    next_id: AtomicUsize,
+    #[cfg(feature = "track-borrows")]
+    registry: Mutex<BTreeMap<BorrowId, BorrowSite>>,
+    /// The address of this count when it last issued a borrow, or zero
+    #[cfg(debug_assertions)]
+    anchor: AtomicUsize
+}
+
+/// Where and on which thread a tracked borrow was created
+#[cfg(feature = "track-borrows")]
+struct BorrowSite {
+    thread: ThreadId,
+    backtrace: Backtrace
+}
+
+impl RefCount {
+    const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            policy: OrderingPolicy::AcquireRelease,
+            limit: usize::MAX,
+            track_peak: false,
+            peak: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            waiting: AtomicBool::new(false),
+            #[cfg(feature = "std")]
+            waiter: Mutex::new(None),
+            #[cfg(feature = "track-borrows")]
+            next_id: AtomicUsize::new(0),
+            #[cfg(feature = "track-borrows")]
+            registry: Mutex::new(BTreeMap::new()),
+            #[cfg(debug_assertions)]
+            anchor: AtomicUsize::new(0)
+        }
+    }
+
+    /// Adds `n` borrows to the count unless that would exceed the limit, recording the
+    /// new peak
+    ///
+    /// Returns the previous value of the count, including the `SEALED` bit, or `None`
+    /// if the borrows couldn't be added.
+    fn try_add(&self, n: usize) -> Option<usize> {
+        self.try_count(n).inspect(|&previous| self.record_peak(previous, n))
+    }
+
+    /// Adds `n` borrows to the count unless that would exceed the limit
+    ///
+    /// Returns the same as `try_add`, but leaves the peak alone, so that borrows which
+    /// are taken back again don't show up in it. Cells without a limit don't need the
+    /// compare-and-swap loop.
+    fn try_count(&self, n: usize) -> Option<usize> {
+        if self.is_uncounted() {
+            return Some(0);
+        }
+        // The owner is alive while it or a borrow exists, so the increment only needs
+        // to be atomic; the `Release`/`Acquire` pair on drop orders the data accesses
+        let previous = if self.limit == usize::MAX {
+            self.count.fetch_add(n, self.policy.relaxed())
+        } else {
+            self.count.fetch_update(self.policy.relaxed(), self.policy.relaxed(), |count| {
+                (count & !SEALED).checked_add(n).filter(|&borrows| borrows <= self.limit).map(|_| count + n)
+            }).ok()?
+        };
+        Some(previous)
+    }
+
+    /// Records the number of borrows after `n` were added to a count of `previous`
+    fn record_peak(&self, previous: usize, n: usize) {
+        if self.track_peak {
+            self.peak.fetch_max((previous & !SEALED) + n, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds `n` borrows to the count, panicking if that would exceed the limit
+    ///
+    /// Used for borrows derived from existing ones, which are allowed on a sealed cell.
+    fn add(&self, n: usize) {
+        if self.try_add(n).is_none() {
+            self.limit_reached();
+        }
+    }
+
+    /// Adds `n` borrows issued by the owner, which fails once the cell is sealed
+    ///
+    /// The sealed bit lives in the count itself, so a borrow racing with `seal` is
+    /// either counted before the cell is sealed or sees the bit and is taken back.
+    fn try_lend(&self, n: usize) -> Result<(), BorrowError> {
+        self.check_not_moved();
+        if self.is_sealed() {
+            return Err(BorrowError::Sealed);
+        }
+        match self.try_count(n) {
+            Some(previous) if previous & SEALED == 0 => {
+                self.record_peak(previous, n);
+                #[cfg(debug_assertions)]
+                self.anchor.store(ptr::from_ref(self).addr(), Ordering::Relaxed);
+                Ok(())
+            }
+            Some(_) => {
+                // A thread draining the cell may be waiting for exactly these borrows
+                self.release_n(n);
+                Err(BorrowError::Sealed)
+            }
+            None => Err(BorrowError::LimitExceeded)
+        }
+    }
+
+    /// Adds `n` borrows issued by the owner, panicking if it is sealed or the limit
+    /// would be exceeded
+    fn lend(&self, n: usize) {
+        match self.try_lend(n) {
+            Ok(()) => {}
+            Err(BorrowError::Sealed) => panic!("Cannot borrow an AtomicLendCell after it has been sealed!"),
+            Err(_) => self.limit_reached()
+        }
+    }
+
+    #[cold]
+    fn limit_reached(&self) -> ! {
+        panic!("The borrow limit of {} for this AtomicLendCell has been reached!", self.limit);
+    }
+
+    /// Panics if the owner was moved while borrows were outstanding
+    ///
+    /// The borrows still point at the old location, so this can't be repaired, only
+    /// reported the next time the owner is used. Moving a cell without borrows is fine,
+    /// as the next borrow records the new address. Only checked in debug builds.
+    #[inline]
+    fn check_not_moved(&self) {
+        #[cfg(debug_assertions)]
+        {
+            let anchor = self.anchor.load(Ordering::Relaxed);
+            if anchor != 0 && anchor != ptr::from_ref(self).addr() && self.count.load(Ordering::Relaxed) & !SEALED > 0 {
+                panic!("An AtomicLendCell was moved while borrowed, leaving its borrows dangling!");
+            }
+        }
+    }
+
+    /// Returns `true` for the stand-in count of borrows created by `borrow_unchecked`
+    #[inline]
+    fn is_uncounted(&self) -> bool {
+        ptr::eq(self, &UNCOUNTED)
+    }
+
+    /// Returns `true` if the owner no longer issues new borrows
+    fn is_sealed(&self) -> bool {
+        self.count.load(Ordering::Relaxed) & SEALED != 0
+    }
+
+    /// Records a new borrow along with the thread and a backtrace of where it was created
+    ///
+    /// Borrows created with `borrow_unchecked` are meant to skip per-borrow work, so they
+    /// all share `BorrowId::UNTRACKED` instead of being recorded.
+    #[cfg(feature = "track-borrows")]
+    fn register(&self) -> BorrowId {
+        if self.is_uncounted() {
+            return BorrowId::UNTRACKED;
+        }
+        let id = BorrowId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let site = BorrowSite {thread: std::thread::current().id(), backtrace: Backtrace::capture()};
+        self.registry.lock().unwrap_or_else(PoisonError::into_inner).insert(id, site);
+        id
+    }
+
+    /// Forgets a borrow that is about to be dropped
+    #[cfg(feature = "track-borrows")]
+    fn unregister(&self, id: BorrowId) {
+        if self.is_uncounted() {
+            return;
+        }
+        self.registry.lock().unwrap_or_else(PoisonError::into_inner).remove(&id);
+    }
+
+    /// Describes the outstanding borrows for the panic message of a dropped owner
+    #[cfg(feature = "track-borrows")]
+    fn report(&self) -> String {
+        let registry = self.registry.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut report = String::from("\nOutstanding borrows:");
+        for (id, site) in registry.iter() {
+            report += &format!("\n{id:?} created on {:?} at:\n{}", site.thread, site.backtrace);
+        }
+        report
+    }
+
+    /// Decrements the count on behalf of a dropped borrow, waking up a waiting thread
+    fn release(&self) {
+        self.release_n(1);
+    }
+
+    /// Decrements the count by `n`, waking up a waiting thread if it drops to zero
+    fn release_n(&self, n: usize) {
+        if self.is_uncounted() {
+            return;
+        }
+        #[cfg(feature = "std")]
+        if self.waiting.load(Ordering::Acquire) {
+            // The cell may be freed as soon as the count reaches zero, so the waiting
+            // thread has to be looked up before decrementing
+            let waiter = self.waiter.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            let previous = self.count.fetch_sub(n, self.policy.release()) & !SEALED;
+            Self::check_underflow(previous, n);
+            if previous == n && let Some(thread) = waiter {
+                thread.unpark();
+            }
+            return;
+        }
+        // Publishes this borrow's reads of the data to the owner's drop
+        let previous = self.count.fetch_sub(n, self.policy.release()) & !SEALED;
+        Self::check_underflow(previous, n);
+    }
+
+    /// Panics in debug builds if a decrement found fewer borrows than it released
+    ///
+    /// This happens when more borrows are dropped than were issued, for example when
+    /// the pointers from `into_raw` are passed to `from_raw` twice.
+    #[inline]
+    fn check_underflow(previous: usize, n: usize) {
+        debug_assert!(
+            previous >= n,
+            "AtomicBorrowCell dropped with a reference count of zero: more borrows were dropped than were created"
+        );
+    }
+
+    /// Blocks the current thread until the count is zero or `expired` returns `true`
+    ///
+    /// Returns `true` if the count has dropped to zero.
+    fn wait(&self, mut expired: impl FnMut() -> bool) -> bool {
+        #[cfg(feature = "std")]
+        {
+            *self.waiter.lock().unwrap_or_else(PoisonError::into_inner) = Some(std::thread::current());
+            self.waiting.store(true, Ordering::Release);
+        }
+        let drained = loop {
+            if self.count.load(self.policy.acquire()) & !SEALED == 0 {
+                break true;
+            }
+            if expired() {
+                break false;
+            }
+            // A borrow that read `waiting` just before it was set will not unpark this
+            // thread, so parking is bounded by a timeout
+            #[cfg(feature = "std")]
+            std::thread::park_timeout(PARK_TIMEOUT);
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        };
+        #[cfg(feature = "std")]
+        {
+            self.waiting.store(false, Ordering::Release);
+            *self.waiter.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        }
+        drained
+    }
+}
+
+/// Identifies an outstanding borrow in the reports of the `track-borrows` feature
+#[cfg(feature = "track-borrows")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BorrowId(usize);
+
+#[cfg(feature = "track-borrows")]
+impl BorrowId {
+    /// The id of every borrow created with `borrow_unchecked`, which isn't tracked
+    const UNTRACKED: BorrowId = BorrowId(usize::MAX);
+}
+
+/// The state of an `AtomicLendCell` as returned by [`AtomicLendCell::state`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellState {
+    /// The cell is in use with the given number of outstanding borrows
+    Alive {
+        borrows: usize
+    },
+    /// A thread is blocked waiting for the given number of borrows to be dropped,
+    /// e.g. in `wait_until_free` or the drop of a blocking cell
+    Waiting {
+        borrows: usize
+    }
+}
+
+/// The memory orderings used for operations on the reference count
+///
+/// Set with [`AtomicLendCell::with_ordering`]. The policy is shared with the borrows,
+/// so borrowing, cloning and dropping borrows, and dropping the owner all follow it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OrderingPolicy {
+    /// Relaxed increments, `Release` decrements and `Acquire` before the owner drops the
+    /// value, which is all that is needed to make the drop wait for the borrows' reads
+    #[default]
+    AcquireRelease,
+    /// `SeqCst` for every operation on the count, so that they are part of the single
+    /// total order of sequentially consistent operations
+    SeqCst
+}
+
+impl OrderingPolicy {
+    /// The ordering for operations that need no synchronization
+    const fn relaxed(self) -> Ordering {
+        match self {
+            OrderingPolicy::AcquireRelease => Ordering::Relaxed,
+            OrderingPolicy::SeqCst => Ordering::SeqCst
+        }
+    }
+
+    /// The ordering for decrements publishing a borrow's reads
+    const fn release(self) -> Ordering {
+        match self {
+            OrderingPolicy::AcquireRelease => Ordering::Release,
+            OrderingPolicy::SeqCst => Ordering::SeqCst
+        }
+    }
+
+    /// The ordering for loads observing dropped borrows
+    const fn acquire(self) -> Ordering {
+        match self {
+            OrderingPolicy::AcquireRelease => Ordering::Acquire,
+            OrderingPolicy::SeqCst => Ordering::SeqCst
+        }
+    }
+}
+
+/// The stand-in reference count of borrows created by `borrow_unchecked`
+///
+/// Borrows pointing at it, and their clones, neither increment nor decrement it.
+static UNCOUNTED: RefCount = RefCount::new();
+
+/// The bit of the reference count that marks a sealed cell
+///
+/// Keeping it in the same word as the count makes sealing and borrowing a single
+/// atomic operation each, so they can't miss each other.
+const SEALED: usize = 1 << (usize::BITS - 1);
+
+/// The longest a thread waiting for borrows stays parked without rechecking the count
+#[cfg(feature = "std")]
+const PARK_TIMEOUT: Duration = Duration::from_millis(1);
+
+impl<T: ?Sized + PartialEq> PartialEq for AtomicLendCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for AtomicLendCell<T> {}
+
+impl<T: ?Sized + PartialEq> PartialEq for AtomicBorrowCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for AtomicBorrowCell<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for AtomicBorrowCell<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.as_ref().partial_cmp(other.as_ref())
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for AtomicBorrowCell<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<AtomicBorrowCell<T>> for AtomicLendCell<T> {
+    fn eq(&self, other: &AtomicBorrowCell<T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<AtomicLendCell<T>> for AtomicBorrowCell<T> {
+    fn eq(&self, other: &AtomicLendCell<T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+/// Allows the cell to be passed to code that is generic over `AsRef<T>`
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// fn takes(x: impl AsRef<i32>) -> i32 {
+///     *x.as_ref()
+/// }
+///
+/// let cell = AtomicLendCell::new(42);
+/// assert_eq!(takes(cell.borrow()), 42);
+/// assert_eq!(takes(&cell), 42);
+/// ```
+impl<T: ?Sized> AsRef<T> for AtomicLendCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for AtomicBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> LendCell<T> for AtomicLendCell<T> {
+    type Borrow = AtomicBorrowCell<T>;
+
+    fn borrow(&self) -> AtomicBorrowCell<T> {
+        AtomicLendCell::borrow(self)
+    }
+
+    fn as_ref(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for AtomicBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for GuardedBorrow<'_, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for CompactBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        CompactBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for ReadView<T> {
+    fn as_ref(&self) -> &T {
+        ReadView::as_ref(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> BorrowHandle<T> for ArcBorrow<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+// `Borrow` is not imported, since its `borrow` would take precedence over the inherent
+// `borrow` of cells behind smart pointers such as `Box`
+impl<T: ?Sized> core::borrow::Borrow<T> for AtomicLendCell<T> {
+    fn borrow(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> core::borrow::Borrow<T> for AtomicBorrowCell<T> {
+    fn borrow(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+// Hashes the contained value, consistent with the `PartialEq` implementations
+impl<T: ?Sized + Hash> Hash for AtomicLendCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for AtomicBorrowCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that borrowing works across threads
+fn test_lambda_borrow(){
+    let x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    let t1 = std::thread::spawn(move ||{
+        let y = xr.as_ref();
+        println!("{:?}", y);
+    });
+    let xr = x.borrow();
+    let t2 = std::thread::spawn(move ||{
+        let y = xr.as_ref();
+        println!("{:?}", y);
+    });
+    t1.join().unwrap();
+    t2.join().unwrap();
+}
+
+#[test]
+/// Tests that the borrow count follows borrows, clones and drops
+fn test_borrow_count() {
+    let x = AtomicLendCell::new(4);
+    assert_eq!(x.borrow_count(), 0);
+    assert!(!x.is_borrowed());
+
+    let b1 = x.borrow();
+    let b2 = x.borrow();
+    let b3 = b1.clone();
+    assert_eq!(x.borrow_count(), 3);
+    assert!(x.is_borrowed());
+
+    drop(b1);
+    assert_eq!(x.borrow_count(), 2);
+    drop(b3);
+    drop(b2);
+    assert_eq!(x.borrow_count(), 0);
+    assert!(!x.is_borrowed());
+}
+
+#[test]
+/// Tests that `into_inner` recovers the value when no borrows remain
+fn test_into_inner() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let b = x.borrow();
+    let c = b.clone();
+    drop(b);
+    drop(c);
+    assert_eq!(x.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+#[should_panic(expected = "outlives")]
+/// Tests that `into_inner` refuses to move the value out while borrows are outstanding
+fn test_into_inner_borrowed() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    std::mem::forget(x.borrow());
+    x.into_inner();
+}
+
+#[test]
+/// Tests that `get_mut` is refused while a borrow is alive
+fn test_get_mut() {
+    let mut x = AtomicLendCell::new(4);
+    let b = x.borrow();
+    assert!(x.get_mut().is_none());
+    drop(b);
+    *x.get_mut().unwrap() = 5;
+    assert_eq!(*x.borrow(), 5);
+}
+
+#[test]
+/// Tests the `Debug` output of the cell and its borrows
+fn test_debug() {
+    let x = AtomicLendCell::new(4);
+    let b = x.borrow();
+    assert_eq!(format!("{:?}", x), "AtomicLendCell { data: 4, refcount: 1 }");
+    assert_eq!(format!("{:?}", b), "AtomicBorrowCell { data: 4 }");
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that dropping a blocking cell waits for a borrow held by another thread
+fn test_blocking_drop() {
+    use std::sync::{Arc, atomic::AtomicBool};
+
+    let released = Arc::new(AtomicBool::new(false));
+    let t;
+    {
+        let x = AtomicLendCell::new_blocking(4);
+        let xr = x.borrow();
+        let released_clone = Arc::clone(&released);
+        t = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_eq!(*xr, 4);
+            released_clone.store(true, Ordering::Release);
+            drop(xr);
+        });
+        // `x` is dropped here and must wait for the thread
+    }
+    assert!(released.load(Ordering::Acquire));
+    t.join().unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that `try_reclaim` hands the cell back when borrows outlive the timeout
+fn test_try_reclaim_timeout() {
+    let x = Box::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    let x = match x.try_reclaim(std::time::Duration::from_millis(20)) {
+        Ok(_) => panic!("try_reclaim succeeded with an outstanding borrow"),
+        Err(x) => x,
+    };
+    assert_eq!(x.borrow_count(), 1);
+    drop(xr);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+#[cfg(feature = "std")]
+/// Tests that `try_reclaim` returns the value once borrows are drained in time
+fn test_try_reclaim_drained() {
+    let x = Box::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    let t = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(*xr, 4);
+    });
+    assert_eq!(x.try_reclaim(std::time::Duration::from_secs(5)).ok(), Some(4));
+    t.join().unwrap();
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests lending a slice borrow out of a cell holding an array
+fn test_unsized_borrow() {
+    let x: Box<AtomicLendCell<[i32]>> = Box::new(AtomicLendCell::new([1, 2, 3, 4]));
+    let xr: AtomicBorrowCell<[i32]> = x.borrow();
+    let t = std::thread::spawn(move || xr.iter().sum::<i32>());
+    assert_eq!(t.join().unwrap(), 10);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests projecting a borrow onto a field without changing the count
+fn test_map() {
+    let x = AtomicLendCell::new((4u32, String::from("four")));
+    let xr = x.borrow().map(|pair| &pair.1);
+    assert_eq!(x.borrow_count(), 1);
+    let t = std::thread::spawn(move || {
+        assert_eq!(*xr, "four");
+    });
+    t.join().unwrap();
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that cells and borrows compare by their contained values
+fn test_eq() {
+    let x = AtomicLendCell::new(4);
+    let y = AtomicLendCell::new(4);
+    let z = AtomicLendCell::new(5);
+    assert!(x == y);
+    assert!(x != z);
+    assert!(x.borrow() == y.borrow());
+    assert!(x.borrow() != z.borrow());
+    assert!(x == y.borrow());
+    assert!(z.borrow() != x);
+}
+
+#[test]
+/// Tests lending a scoped borrow to scoped threads
+fn test_with_scope() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let sum = x.with_scope(|xr| {
+        std::thread::scope(|s| {
+            let t1 = s.spawn(move || xr.iter().sum::<i32>());
+            let t2 = s.spawn(move || xr.len() as i32);
+            t1.join().unwrap() + t2.join().unwrap()
+        })
+    });
+    assert_eq!(sum, 9);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that a replaced value is seen by subsequent borrows
+fn test_replace() {
+    let mut x = AtomicLendCell::new(String::from("old"));
+    drop(x.borrow());
+    assert_eq!(x.replace(String::from("new")), "old");
+    let xr = x.borrow();
+    let t = std::thread::spawn(move || assert_eq!(*xr, "new"));
+    t.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "outstanding borrows")]
+/// Tests that replacing the value of a borrowed cell panics
+fn test_replace_borrowed() {
+    let mut x = AtomicLendCell::new(4);
+    let _xr = x.borrow();
+    x.replace(5);
+}
+
+#[test]
+/// Tests that a cell can be shared whenever its value can, even if the value can't be sent
+fn test_sync_without_send() {
+    fn assert_sync<S: Sync>() {}
+    assert_sync::<AtomicLendCell<std::sync::MutexGuard<'static, i32>>>();
+}
+
+#[test]
+/// Tests that borrows of equal values are deduplicated in a `HashSet`
+fn test_hash() {
+    use std::collections::HashSet;
+
+    let x = AtomicLendCell::new(String::from("shared"));
+    let y = AtomicLendCell::new(String::from("shared"));
+    let z = AtomicLendCell::new(String::from("other"));
+    let set: HashSet<_> = [x.borrow(), y.borrow(), x.borrow(), z.borrow()].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&String::from("shared")));
+}
+
+#[test]
+#[cfg(feature = "abort-on-violation")]
+/// Tests that a borrow outliving its owner aborts the process
+///
+/// The test re-runs itself in a child process, which performs the violation.
+fn test_abort_on_violation() {
+    const CHILD_ENV: &str = "ATOMIC_LEND_CELL_ABORT_CHILD";
+    if std::env::var_os(CHILD_ENV).is_some() {
+        let x = AtomicLendCell::new(4);
+        std::mem::forget(x.borrow());
+        return;
+    }
+
+    let status = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "atomic_counting::test_abort_on_violation", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        // SIGABRT
+        assert_eq!(status.signal(), Some(6));
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that `wait_until_free` returns only after all borrowing threads are done
+fn test_wait_until_free() {
+    use std::sync::atomic::AtomicUsize;
+
+    let finished = AtomicUsize::new(0);
+    let x = AtomicLendCell::new(4);
+    std::thread::scope(|s| {
+        for i in 0..4 {
+            let xr = x.borrow();
+            let finished = &finished;
+            s.spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(10 * i));
+                assert_eq!(*xr, 4);
+                finished.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        x.wait_until_free();
+        assert_eq!(finished.load(Ordering::Relaxed), 4);
+        assert!(!x.is_borrowed());
     });
+}
+
+#[test]
+/// Tests that a fresh borrow points at the cell's value
+fn test_as_ptr() {
+    let x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    assert_eq!(x.as_ptr(), xr.as_ptr());
+    assert_eq!(unsafe { *xr.as_ptr() }, 4);
+}
+
+#[test]
+/// Tests that a round trip through raw pointers keeps the count balanced
+fn test_raw_round_trip() {
+    let x = AtomicLendCell::new(String::from("raw"));
+    let (data, rc) = x.borrow().into_raw();
+    assert_eq!(x.borrow_count(), 1);
+    let xr = unsafe { AtomicBorrowCell::from_raw(data, rc) };
+    assert_eq!(x.borrow_count(), 1);
+    assert_eq!(*xr, "raw");
+    drop(xr);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests using the cell through the backend traits
+fn test_traits() {
+    fn sum<C: LendCell<[i32; 2]> + ?Sized>(cell: &C) -> i32 {
+        let borrow = cell.borrow();
+        borrow.as_ref()[0] + cell.as_ref()[1]
+    }
+    let x = AtomicLendCell::new([1, 2]);
+    assert_eq!(sum(&x), 3);
+
+    let dynamic: &dyn LendCell<[i32; 2], Borrow = AtomicBorrowCell<[i32; 2]>> = &x;
+    assert_eq!(sum(dynamic), 3);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests borrowing across threads with each ordering policy
+fn test_ordering_policies() {
+    for policy in [OrderingPolicy::AcquireRelease, OrderingPolicy::SeqCst] {
+        let x = AtomicLendCell::new(4).with_ordering(policy);
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let xr = x.borrow();
+                std::thread::spawn(move || assert_eq!(*xr.clone(), 4))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(x.borrow_count(), 0);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that borrows created in bulk are released individually
+fn test_borrow_many() {
+    let x = AtomicLendCell::new(4);
+    let mut borrows = x.borrow_many(1000);
+    assert_eq!(x.borrow_count(), 1000);
+    assert!(borrows.iter().all(|xr| **xr == 4));
+    borrows.truncate(10);
+    assert_eq!(x.borrow_count(), 10);
+    drop(borrows);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests swapping the values of two quiescent cells
+fn test_swap() {
+    let mut x = AtomicLendCell::new(String::from("front"));
+    let mut y = AtomicLendCell::new(String::from("back"));
+    drop(x.borrow());
+    x.swap(&mut y);
+    assert_eq!((x.as_ref().as_str(), y.as_ref().as_str()), ("back", "front"));
+}
+
+#[test]
+#[should_panic(expected = "outstanding borrows")]
+/// Tests that swapping with a borrowed cell panics
+fn test_swap_borrowed() {
+    let mut x = AtomicLendCell::new(4);
+    let mut y = AtomicLendCell::new(5);
+    let _yr = y.borrow();
+    x.swap(&mut y);
+}
+
+#[test]
+/// Tests that `take` returns the value and leaves the default behind
+fn test_take() {
+    let mut x = AtomicLendCell::new(vec![1, 2, 3]);
+    assert_eq!(x.take(), vec![1, 2, 3]);
+    assert!(x.borrow().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "outstanding borrows")]
+/// Tests that taking the value of a borrowed cell panics
+fn test_take_borrowed() {
+    let mut x = AtomicLendCell::new(vec![1, 2, 3]);
+    let _xr = x.borrow();
+    x.take();
+}
+
+#[test]
+/// Tests that the value can be taken again once the borrows have been released
+fn test_take_after_borrows_released() {
+    let mut x = AtomicLendCell::new(vec![1, 2, 3]);
     let xr = x.borrow();
-    let t2 = std::thread::spawn(move ||{
-        let y = xr.as_ref();
-        println!("{:?}", y);
+    assert_eq!(xr.len(), 3);
+    drop(xr);
+    assert_eq!(x.take(), vec![1, 2, 3]);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that a cloned cell shares neither the value nor the borrow count
+fn test_clone_cell() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let _xr = x.borrow();
+    let y = x.clone();
+    assert_eq!(y.borrow_count(), 0);
+
+    let yr = (y.borrow(), y.borrow());
+    assert_eq!((x.borrow_count(), y.borrow_count()), (1, 2));
+    assert_eq!(*yr.0, *x);
+    assert_ne!(x.as_ptr(), y.as_ptr());
+}
+
+#[test]
+/// Tests both outcomes of projecting a borrow with `filter_map`
+fn test_filter_map() {
+    enum Shape {
+        Circle(f64),
+        Square(f64)
+    }
+    let x = AtomicLendCell::new([Shape::Circle(1.5), Shape::Square(2.0)]);
+    let square = x.borrow().map(|shapes| &shapes[0]).filter_map(|shape| match shape {
+        Shape::Square(side) => Some(side),
+        _ => None
+    });
+    let xr = square.err().unwrap();
+    let radius = xr.filter_map(|shape| match shape {
+        Shape::Circle(radius) => Some(radius),
+        _ => None
     });
+    assert_eq!(*radius.ok().unwrap(), 1.5);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests handing the halves of a split borrow to separate threads
+fn test_split() {
+    let x = AtomicLendCell::new((4, String::from("four")));
+    let (number, text) = x.borrow().split(|pair| (&pair.0, pair.1.as_str()));
+    assert_eq!(x.borrow_count(), 2);
+    let t1 = std::thread::spawn(move || assert_eq!(*number, 4));
+    let t2 = std::thread::spawn(move || assert_eq!(&*text, "four"));
     t1.join().unwrap();
     t2.join().unwrap();
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that `Display` forwards to the value
+fn test_display() {
+    let x = AtomicLendCell::new(String::from("shown"));
+    assert_eq!(format!("{:>6}|{}", x, x.borrow()), " shown|shown");
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that a borrow of an owned cell keeps reading after the owner is dropped
+fn test_new_owned_outlives_owner() {
+    let x = AtomicLendCell::new_owned(String::from("survivor"));
+    let xr = x.borrow();
+    drop(x);
+    let t = std::thread::spawn(move || {
+        assert!(xr.is_poisoned());
+        assert_eq!(*xr, "survivor");
+    });
+    t.join().unwrap();
+}
+
+#[test]
+/// Tests the state reported as borrows come and go
+fn test_state() {
+    let x = AtomicLendCell::new(4);
+    assert_eq!(x.state(), CellState::Alive { borrows: 0 });
+    let xr = x.borrow();
+    let xr2 = xr.clone();
+    assert_eq!(x.state(), CellState::Alive { borrows: 2 });
+    drop(xr);
+    assert_eq!(format!("{:?}", x.state()), "Alive { borrows: 1 }");
+    drop(xr2);
+    assert_eq!(x.state(), CellState::Alive { borrows: 0 });
+}
+
+#[test]
+#[cfg(feature = "track-borrows")]
+/// Tests that leaked borrows are listed by `outstanding`
+fn test_outstanding() {
+    // The leaked borrow would make the owner's drop panic
+    let x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let kept = x.borrow();
+    let leaked = x.borrow();
+    let leaked_id = leaked.id();
+    mem::forget(leaked);
+    let mapped = kept.clone().map(|value| value);
+    drop(kept);
+
+    assert_eq!(x.outstanding(), [leaked_id, mapped.id()]);
+    drop(mapped);
+    assert_eq!(x.outstanding(), [leaked_id]);
+
+    let report = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| borrows_outlive_owner(&x.refcount, "i32")));
+    let message = report.unwrap_err().downcast::<String>().unwrap();
+    let thread = std::thread::current().id();
+    assert!(message.contains(&format!("{leaked_id:?} created on {thread:?} at:")));
+}
+
+#[test]
+/// Tests lending a value that is not `Sync` to a single thread
+fn test_borrow_exclusive() {
+    use std::cell::Cell;
+
+    let mut x = AtomicLendCell::new(Cell::new(4));
+    let xr = x.borrow_exclusive().unwrap();
+    std::thread::scope(|s| s.spawn(move || xr.set(xr.get() + 1)).join().unwrap());
+    assert_eq!(x.get(), 5);
+    assert_eq!(x.borrow_count(), 0);
+
+    let shared = x.borrow();
+    assert!(x.borrow_exclusive().is_none());
+    drop(shared);
+    assert!(x.borrow_exclusive().is_some());
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests borrowing from a cell in a static
+fn test_static() {
+    static CONFIG: AtomicLendCell<u32> = AtomicLendCell::new(7);
+    let xr = CONFIG.borrow();
+    assert_eq!(CONFIG.borrow_count(), 1);
+    let t = std::thread::spawn(move || assert_eq!(*xr, 7));
+    t.join().unwrap();
+    assert_eq!(CONFIG.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that `ptr_eq` compares the borrowed addresses rather than the values
+fn test_ptr_eq() {
+    let x = AtomicLendCell::new(4);
+    let y = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    assert!(AtomicBorrowCell::ptr_eq(&xr, &xr.clone()));
+    assert!(!AtomicBorrowCell::ptr_eq(&xr, &y.borrow()));
+    assert_eq!(xr, y.borrow());
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests spawning workers that each get a borrow
+fn test_scope() {
+    let x = AtomicLendCell::new(String::from("shared"));
+    let lengths: Vec<usize> = x.scope(|s| {
+        let workers: Vec<_> = (0..3).map(|_| s.spawn_with_borrow(|xr| xr.len())).collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+    assert_eq!(lengths, [6, 6, 6]);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "reference count of zero")]
+/// Tests that dropping more borrows than were created is detected
+fn test_release_underflow() {
+    let x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let (data, rc) = x.borrow().into_raw();
+    let first = unsafe { AtomicBorrowCell::from_raw(data, rc) };
+    let second = unsafe { AtomicBorrowCell::from_raw(data, rc) };
+    drop(first);
+    drop(second);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that returning a lease releases its borrow
+fn test_lease_returned() {
+    let x = AtomicLendCell::new(4);
+    let lease = x.lease();
+    let t = std::thread::spawn(move || {
+        assert_eq!(*lease, 4);
+        lease.return_to_owner();
+    });
+    t.join().unwrap();
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Lease dropped without being returned")]
+/// Tests that dropping a lease without returning it panics in debug builds
+fn test_lease_dropped() {
+    let x = AtomicLendCell::new(4);
+    let lease = x.lease();
+    drop(lease);
+}
+
+#[test]
+/// Tests several reads of an atomic value inside one `with` call
+fn test_with() {
+    let x = AtomicLendCell::new(AtomicUsize::new(1));
+    let xr = x.borrow();
+    let sum = xr.with(|counter| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        counter.fetch_add(1, Ordering::Relaxed);
+        counter.load(Ordering::Relaxed) + counter.load(Ordering::Relaxed)
+    });
+    assert_eq!(sum, 6);
+    assert_eq!(x.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that a boxed value stays at the same address when lent
+fn test_from_box() {
+    let payload = vec![7u8; 1 << 20].into_boxed_slice();
+    let address = payload.as_ptr();
+    let x = AtomicLendCell::from_box(payload);
+    assert_eq!(x.as_ptr(), address);
+    let xr = x.borrow();
+    assert_eq!(xr.as_ptr().cast::<u8>(), address);
+    assert_eq!(xr[1 << 19], 7);
+    drop(xr);
+    let payload = x.into_box();
+    assert_eq!(payload.as_ptr(), address);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that `try_drop` drops a cell that isn't borrowed
+fn test_try_drop() {
+    use std::rc::Rc;
+
+    let value = Rc::new(4);
+    let x = Box::new(AtomicLendCell::new(Rc::clone(&value)));
+    assert!(x.try_drop().is_ok());
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that `try_drop` hands back a borrowed cell along with its borrow count
+fn test_try_drop_borrowed() {
+    let x = Box::new(AtomicLendCell::new(4));
+    let borrows = [x.borrow(), x.borrow()];
+    let (x, outstanding) = x.try_drop().unwrap_err();
+    assert_eq!(outstanding, 2);
+    assert_eq!(*borrows[1], 4);
+    drop(borrows);
+    assert!(x.try_drop().is_ok());
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests handing out non-overlapping chunks of a vector to several threads
+fn test_borrow_range() {
+    let x = AtomicLendCell::new((0..12).collect::<Vec<u8>>());
+    let workers: Vec<_> = (0..3)
+        .map(|i| {
+            let chunk = x.borrow_range(i * 4..i * 4 + 4);
+            std::thread::spawn(move || chunk.iter().map(|&b| b as u32).sum::<u32>())
+        })
+        .collect();
+    let sums: Vec<u32> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+    assert_eq!(sums, [6, 22, 38]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "out of bounds")]
+/// Tests that borrowing a range past the end of the vector panics
+fn test_borrow_range_out_of_bounds() {
+    let x = AtomicLendCell::new(vec![1u8, 2, 3]);
+    let _xr = x.borrow_range(2..4);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that the owner sees every borrow released by threads that were joined
+fn test_drop_after_concurrent_borrows() {
+    for _ in 0..100 {
+        let x = AtomicLendCell::new(vec![1, 2, 3]);
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let xr = x.borrow();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        let clone = xr.clone();
+                        assert_eq!(clone.len(), 3);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        drop(x);
+    }
+}
+
+#[test]
+/// Tests sorting borrows by their values
+fn test_sort_borrows() {
+    let a = AtomicLendCell::new("pear");
+    let b = AtomicLendCell::new("apple");
+    let c = AtomicLendCell::new("fig");
+    let mut borrows = [a.borrow(), b.borrow(), c.borrow()];
+    borrows.sort();
+    let sorted: Vec<&str> = borrows.iter().map(|xr| **xr).collect();
+    assert_eq!(sorted, ["apple", "fig", "pear"]);
+    assert!(a.borrow() > b.borrow());
+}
+
+#[test]
+/// Tests that a cell with a limit refuses borrows past it until one is dropped
+fn test_with_limit() {
+    let x = AtomicLendCell::with_limit(4, 3);
+    let first = x.borrow();
+    let second = x.try_borrow().unwrap();
+    let third = first.try_clone().unwrap();
+    assert_eq!(x.try_borrow().unwrap_err(), BorrowError::LimitExceeded);
+    assert!(second.try_clone().is_none());
+    assert_eq!(x.borrow_count(), 3);
+    drop(second);
+    let fourth = x.try_borrow().unwrap();
+    assert_eq!(x.borrow_count(), 3);
+    drop((first, third, fourth));
+}
+
+#[test]
+#[should_panic(expected = "borrow limit of 1")]
+/// Tests that cloning a borrow past the limit panics
+fn test_with_limit_clone() {
+    let x = AtomicLendCell::with_limit(4, 1);
+    let xr = x.borrow();
+    let _ = xr.clone();
+}
+
+#[test]
+/// Tests that the limit holds when many threads borrow concurrently
+fn test_with_limit_contended() {
+    let x = AtomicLendCell::with_limit(4, 4);
+    let peak = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    if let Ok(xr) = x.try_borrow() {
+                        peak.fetch_max(x.borrow_count(), Ordering::Relaxed);
+                        drop(xr);
+                    }
+                }
+            });
+        }
+    });
+    assert!(peak.load(Ordering::Relaxed) <= 4);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that the peak borrow count is kept after the borrows are dropped
+fn test_with_high_water() {
+    let x = AtomicLendCell::with_high_water(4);
+    assert_eq!(x.peak_borrows(), 0);
+    let xr = x.borrow();
+    let burst: Vec<_> = (0..4).map(|_| xr.clone()).chain(x.borrow_many(3)).collect();
+    assert_eq!(x.peak_borrows(), 8);
+    drop(burst);
+    drop(xr);
+    assert_eq!(x.borrow_count(), 0);
+    assert_eq!(x.peak_borrows(), 8);
+    let _xr = x.borrow();
+    assert_eq!(x.peak_borrows(), 8);
+}
+
+#[test]
+/// Tests that the peak is recorded when threads borrow concurrently
+fn test_with_high_water_contended() {
+    let x = AtomicLendCell::with_high_water(4);
+    let barrier = std::sync::Barrier::new(4);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let xr = x.borrow();
+                barrier.wait();
+                drop(xr);
+            });
+        }
+    });
+    assert_eq!(x.peak_borrows(), 4);
+}
+
+#[test]
+/// Tests that borrows of a zero-sized value are counted like any other
+fn test_zero_sized() {
+    let x = AtomicLendCell::new(());
+    let xr = x.borrow();
+    let xr2 = xr.clone();
+    assert_eq!(*xr2, ());
+    assert_eq!(x.borrow_count(), 2);
+    let (data, rc) = xr.into_raw();
+    assert!(!data.is_null());
+    drop(unsafe { AtomicBorrowCell::from_raw(data, rc) });
+    drop(xr2);
+    assert_eq!(x.borrow_count(), 0);
+    x.into_inner();
+
+    let y = AtomicLendCell::new((4, ()));
+    let yr = y.borrow().map(|pair| &pair.1);
+    assert_eq!(y.borrow_count(), 1);
+    assert_eq!(format!("{:?}", yr), "AtomicBorrowCell { data: () }");
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+#[should_panic(expected = "outlives")]
+/// Tests that a cell with a zero-sized value still refuses to outlive its borrows
+fn test_zero_sized_outlive() {
+    let x = AtomicLendCell::new(());
+    std::mem::forget(x.borrow());
+    x.into_inner();
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that a sealed cell refuses new borrows while existing ones stay valid
+fn test_seal() {
+    let mut x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    assert!(!x.is_sealed());
+    x.seal();
+    assert!(x.is_sealed());
+    assert_eq!(x.try_borrow().unwrap_err(), BorrowError::Sealed);
+    assert!(x.borrow_exclusive().is_none());
+    let xr2 = xr.clone();
+    assert_eq!(x.borrow_count(), 2);
+    let t = std::thread::spawn(move || assert_eq!(*xr2, 4));
+    t.join().unwrap();
+    drop(xr);
+    x.wait_until_free();
+    assert_eq!(x.borrow_count(), 0);
+    assert!(x.is_sealed());
+}
+
+#[test]
+#[should_panic(expected = "sealed")]
+/// Tests that borrowing a sealed cell panics
+fn test_seal_borrow() {
+    let x = AtomicLendCell::new(4);
+    x.seal();
+    let _xr = x.borrow();
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests draining a cell that threads keep borrowing until it is sealed
+fn test_seal_drain() {
+    for _ in 0..100 {
+        let x = Box::new(AtomicLendCell::new(4));
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| while let Ok(xr) = x.try_borrow() {
+                    assert_eq!(*xr, 4);
+                });
+            }
+            x.seal();
+            x.wait_until_free();
+        });
+        assert!(x.try_drop().is_ok());
+    }
+}
+
+#[test]
+/// Tests that a sealed cell at its limit reports being sealed
+fn test_try_borrow_errors() {
+    let x = AtomicLendCell::with_limit(4, 1);
+    let xr = x.try_borrow().unwrap();
+    assert_eq!(x.try_borrow().unwrap_err(), BorrowError::LimitExceeded);
+    x.seal();
+    assert_eq!(x.try_borrow().unwrap_err(), BorrowError::Sealed);
+    drop(xr);
+    assert_eq!(x.try_borrow().unwrap_err().to_string(), "the owner has been sealed against new borrows");
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that `clone_n` adds exactly `n` borrows, which are released with the vector
+fn test_clone_n() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let xr = x.borrow();
+    let clones = xr.clone_n(5);
+    assert_eq!(x.borrow_count(), 6);
+    assert!(clones.iter().all(|clone| AtomicBorrowCell::ptr_eq(clone, &xr)));
+    drop(clones);
+    assert_eq!(x.borrow_count(), 1);
+    assert!(xr.clone_n(0).is_empty());
+    assert_eq!(x.borrow_count(), 1);
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests handing each element of a vector to a different thread
+fn test_borrow_each() {
+    let x = AtomicLendCell::new((1..=10).collect::<Vec<i32>>());
+    let workers: Vec<_> = x
+        .borrow_each()
+        .map(|element| std::thread::spawn(move || *element * 2))
+        .collect();
+    let sum: i32 = workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+    assert_eq!(sum, 110);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that `borrow_each` adds one borrow per yielded element
+fn test_borrow_each_count() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let mut elements = x.borrow_each();
+    assert_eq!(x.borrow_count(), 0);
+    let first = elements.next().unwrap();
+    assert_eq!(x.borrow_count(), 1);
+    let rest: Vec<_> = elements.collect();
+    assert_eq!(x.borrow_count(), 3);
+    assert_eq!((*first, *rest[1]), (1, 3));
+    drop((first, rest));
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests lending the value of a nested cell to another thread
+fn test_borrow_inner() {
+    let x = AtomicLendCell::new(AtomicLendCell::new(String::from("inner")));
+    let xr = x.borrow_inner();
+    let t = std::thread::spawn(move || xr.len());
+    assert_eq!(t.join().unwrap(), 5);
+    let layered = AtomicLendCell::new(AtomicLendCell::new(AtomicLendCell::new(7)));
+    let inner = layered.borrow().map(|outer| outer.as_ref());
+    assert_eq!(**inner, 7);
+}
+
+#[test]
+/// Tests that the borrow of a nested value is counted by the outer cell
+fn test_borrow_inner_count() {
+    let x = AtomicLendCell::new(AtomicLendCell::new(4));
+    let xr = x.borrow_inner();
+    assert_eq!((x.borrow_count(), x.as_ref().borrow_count()), (1, 0));
+    drop(xr);
+    assert_eq!(x.into_inner().into_inner(), 4);
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+/// Tests that the panic of an owner dropped while borrowed names the value's type
+fn test_outlive_type_name() {
+    let x = AtomicLendCell::new(vec![1u8]);
+    mem::forget(x.borrow());
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| x.into_inner()));
+    let message = panic.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("AtomicBorrowCell<alloc::vec::Vec<u8>>"), "{message}");
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that unchecked borrows and their clones leave the count untouched
+fn test_borrow_unchecked() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let counted = x.borrow();
+    unsafe {
+        let xr = x.borrow_unchecked();
+        let clones = xr.clone_n(2);
+        let first = xr.clone().map(|data| &data[0]);
+        assert_eq!(x.borrow_count(), 1);
+        assert_eq!((*first, clones[1][2]), (1, 3));
+        drop((xr, clones, first));
+    }
+    assert_eq!(x.borrow_count(), 1);
+    drop(counted);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(feature = "track-borrows")]
+/// Tests that unchecked borrows are not recorded by the stand-in count
+fn test_borrow_unchecked_untracked() {
+    let x = AtomicLendCell::new(4);
+    let xr = unsafe { x.borrow_unchecked() };
+    let xr2 = xr.clone();
+    assert_eq!((xr.id(), xr2.id()), (BorrowId::UNTRACKED, BorrowId::UNTRACKED));
+    assert!(UNCOUNTED.registry.lock().unwrap().is_empty());
+    drop((xr, xr2));
+}
+
+#[test]
+/// Tests reading a borrow several times through a guard
+fn test_as_guard() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let xr = x.borrow();
+    let guard = xr.as_guard();
+    let total: i32 = guard.iter().sum::<i32>() + guard[2] + guard.len() as i32;
+    assert_eq!(total, 12);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that compact borrows are counted and read the value from another thread
+fn test_borrow_compact() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let xr = x.borrow_compact();
+    let xr2 = xr.clone();
+    assert_eq!(x.borrow_count(), 2);
+    let t = std::thread::spawn(move || xr2.iter().sum::<i32>());
+    assert_eq!(t.join().unwrap(), 6);
+    let second = AtomicBorrowCell::from(xr).map(|data| &data[1]);
+    assert_eq!(*second, 2);
+    assert_eq!(x.borrow_count(), 1);
+    drop(second);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+#[cfg(not(feature = "track-borrows"))]
+/// Tests that a compact borrow of a sized value is a single pointer wide
+fn test_borrow_compact_size() {
+    use std::mem::size_of;
+
+    assert_eq!(size_of::<CompactBorrowCell<u64>>(), size_of::<*const ()>());
+    assert_eq!(size_of::<CompactBorrowCell<Vec<u8>>>(), size_of::<*const ()>());
+    assert_eq!(size_of::<Option<CompactBorrowCell<u64>>>(), size_of::<*const ()>());
+    assert_eq!(size_of::<AtomicBorrowCell<u64>>(), 2 * size_of::<*const ()>());
+}
+
+#[test]
+#[cfg(all(feature = "track-borrows", not(feature = "no-send")))]
+/// Tests that the outlive report names the thread that created a leaked borrow
+fn test_tracked_thread() {
+    let x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    let worker = std::thread::spawn(move || {
+        mem::forget(xr.clone());
+        std::thread::current().id()
+    });
+    let worker_id = worker.join().unwrap();
+
+    let report = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| borrows_outlive_owner(&x.refcount, "i32")));
+    let message = report.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains(&format!("created on {worker_id:?} at:")), "{message}");
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+#[cfg(feature = "std")]
+/// Tests that an `ArcBorrow` keeps the cell alive and counted after the original `Arc` is dropped
+fn test_arc_borrow_outlives_arc() {
+    let x = Arc::new(AtomicLendCell::new(String::from("shared")));
+    let xr = ArcLendExt::borrow(&x);
+    let xr2 = xr.clone();
+    drop(x);
+    assert_eq!(ArcBorrow::owner(&xr).borrow_count(), 2);
+    drop(xr);
+    let handle = std::thread::spawn(move || xr2.len());
+    assert_eq!(handle.join().unwrap(), 6);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+#[cfg(feature = "std")]
+/// Tests that borrowing a map entry keeps the count balanced on both hits and misses
+fn test_get_map_entry() {
+    let x = AtomicLendCell::new(HashMap::from([(1, String::from("one")), (2, String::from("two"))]));
+    let entry = x.borrow().get(&2).unwrap();
+    assert_eq!(x.borrow_count(), 1);
+    assert!(x.borrow().get(&3).is_none());
+    assert_eq!(x.borrow_count(), 1);
+    let handle = std::thread::spawn(move || entry.as_str().to_owned());
+    assert_eq!(handle.join().unwrap(), "two");
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that a projected borrow shares its owner with its source, but not with another cell
+fn test_same_owner() {
+    let x = AtomicLendCell::new((1, String::from("one")));
+    let y = AtomicLendCell::new((1, String::from("one")));
+    let xr = x.borrow();
+    let field = xr.clone().map(|pair| &pair.1);
+    let yr = y.borrow();
+    assert!(AtomicBorrowCell::same_owner(&xr, &field));
+    assert!(AtomicBorrowCell::same_owner(&field, &xr));
+    assert!(!AtomicBorrowCell::same_owner(&xr, &yr));
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that a `ReadView` reads the value and is `Send` and `Sync` like the borrow it wraps
+fn test_read_view() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let view = x.borrow().read_view();
+    assert_send_sync(&view);
+    assert_eq!(view.as_ref(), &[1, 2, 3]);
+    let handle = std::thread::spawn(move || view.iter().sum::<i32>());
+    assert_eq!(handle.join().unwrap(), 6);
+}
+
+#[test]
+/// Tests that the value's address is stable for a cell that stays in place, but not across a move
+fn test_address_stable_until_moved() {
+    let x = AtomicLendCell::new([0u8; 64]);
+    let first = x.borrow().as_ptr();
+    let xr = x.borrow();
+    assert_eq!(xr.as_ptr(), first);
+    assert_eq!(x.as_ptr(), first);
+    drop(xr);
+    // With no borrows outstanding the cell may be moved, which changes the address
+    let moved = std::vec![x];
+    assert_ne!(moved[0].as_ptr(), first);
+    // Keeping the cell in a box makes the address survive moving the box
+    let boxed = std::boxed::Box::new(AtomicLendCell::new([0u8; 64]));
+    let xr = boxed.borrow();
+    let moved_box = boxed;
+    assert_eq!(xr.as_ptr(), moved_box.as_ptr());
+    drop(xr);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "moved while borrowed")]
+/// Tests that moving a borrowed cell is caught the next time it is borrowed
+fn test_move_while_borrowed() {
+    let x = ManuallyDrop::new(AtomicLendCell::new(4));
+    // The borrow dangles once the cell is moved, so it must not be dropped
+    mem::forget(x.borrow());
+    let moved = std::vec![x];
+    let _ = moved[0].borrow();
+}
+
+#[test]
+/// Tests that a cell may be moved once its borrows have been dropped
+fn test_move_after_borrows_dropped() {
+    let x = AtomicLendCell::new(4);
+    drop(x.borrow());
+    let moved = std::vec![x];
+    assert_eq!(*moved[0].borrow(), 4);
+    assert_eq!(*moved[0], 4);
+}
+
+#[test]
+/// Tests transforming the value of an unborrowed cell into a new cell
+fn test_map_owner() {
+    let x = AtomicLendCell::with_limit(42u32, 1);
+    drop(x.borrow());
+    let y = x.map_owner(|n| n.to_string());
+    let yr = y.borrow();
+    let yr2 = yr.clone();
+    assert_eq!(yr.as_str(), "42");
+    assert_eq!(y.borrow_count(), 2);
+    drop((yr, yr2));
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+#[should_panic(expected = "outlives")]
+/// Tests that mapping a borrowed cell panics instead of calling the closure
+fn test_map_owner_borrowed() {
+    let x = AtomicLendCell::new(42u32);
+    mem::forget(x.borrow());
+    x.map_owner(|_| -> u32 { unreachable!() });
+}
+
+#[test]
+/// Tests reading through guarded borrows and their count
+fn test_borrow_guarded() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let guard = x.borrow_guarded();
+    let guard2 = guard.clone();
+    assert_eq!(guard.len(), 3);
+    assert_eq!(guard2[2], 3);
+    assert_eq!(x.borrow_count(), 2);
+    drop((guard, guard2));
+    assert!(!x.is_borrowed());
+}
+
+#[test]
+/// Tests that panicking projections release the borrows they consumed
+fn test_projection_panic_releases_borrow() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    let x = AtomicLendCell::new((1, 2));
+    let xr = x.borrow();
+    assert!(catch_unwind(AssertUnwindSafe(|| x.borrow().map(|_| -> &i32 { panic!("projection") }))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| x.borrow().filter_map(|_| -> Option<&i32> { panic!("projection") }))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| x.borrow().split(|_| -> (&i32, &i32) { panic!("projection") }))).is_err());
+    assert_eq!(x.borrow_count(), 1);
+    drop(xr);
+}
+
+#[test]
+/// Tests that a value cloned out of a borrow is independent of the cell
+fn test_to_owned() {
+    let mut x = AtomicLendCell::new(String::from("before"));
+    let xr = x.borrow();
+    let mut snapshot = xr.to_owned();
+    drop(xr);
+    snapshot.push_str(" and after");
+    x.get_mut().unwrap().replace_range(.., "changed");
+    assert_eq!(snapshot, "before and after");
+    assert_eq!(x.as_ref(), "changed");
+}
+
+#[test]
+/// Tests reads through every way of deriving a borrow's pointer
+///
+/// No pointer is derived from an integer, so this also passes under
+/// `MIRIFLAGS=-Zmiri-strict-provenance cargo +nightly miri test`.
+fn test_pointer_provenance() {
+    let x = AtomicLendCell::new((String::from("first"), [1u8, 2, 3]));
+    let (data, owner) = x.borrow().map(|pair| &pair.1[1..]).into_raw();
+    let tail = unsafe { AtomicBorrowCell::from_raw(data, owner) };
+    assert_eq!(*tail, [2, 3]);
+    assert_eq!(x.borrow().map(|pair| pair.0.as_str()).as_ref(), "first");
+    drop(tail);
+
+    let value = 7;
+    let y = AtomicLendCell::new(&value);
+    assert_eq!(*y.borrow_deref(), 7);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests reserving a borrow on one thread and claiming it on another
+fn test_reserve_and_claim_on_worker() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let ticket = x.reserve();
+    assert_eq!(x.borrow_count(), 1);
+    let sum = std::thread::scope(|s| {
+        s.spawn(move || {
+            let xr = ticket.claim();
+            xr.iter().sum::<i32>()
+        }).join().unwrap()
+    });
+    assert_eq!(sum, 6);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests that dropping an unclaimed ticket releases its reservation
+fn test_unclaimed_ticket() {
+    let x = AtomicLendCell::new(4);
+    let ticket = x.reserve();
+    assert_eq!(x.borrow_count(), 1);
+    drop(ticket);
+    assert_eq!(x.borrow_count(), 0);
 }
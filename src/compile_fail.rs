@@ -0,0 +1,271 @@
+//! # Compile-Fail Tests
+//!
+//! Misuse that the type system is expected to reject. Each case is a `compile_fail`
+//! doctest paired with the closest variant that does compile, so that a case can't
+//! pass by failing for an unrelated reason. The module is only built by rustdoc.
+
+/// A borrow of a value that is not `Sync` cannot be sent to another thread, in either
+/// implementation:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let cell = AtomicLendCell::new(Cell::new(1));
+/// let borrow = cell.borrow();
+/// std::thread::spawn(move || borrow.get()).join().unwrap();
+/// ```
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let cell = AtomicLendCell::new(Cell::new(1));
+/// let borrow = cell.borrow();
+/// std::thread::spawn(move || borrow.get()).join().unwrap();
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::sync::atomic::{AtomicI32, Ordering};
+///
+/// let cell = AtomicLendCell::new(AtomicI32::new(1));
+/// let borrow = cell.borrow();
+/// std::thread::spawn(move || borrow.load(Ordering::Relaxed)).join().unwrap();
+/// ```
+#[cfg(not(feature = "no-send"))]
+pub struct BorrowOfNonSyncIsNotSend;
+
+/// A `ScopedBorrow` cannot be returned from the closure it was passed to:
+///
+/// ```compile_fail
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let escaped = cell.with_scope(|borrow| borrow);
+/// ```
+///
+/// ```compile_fail
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let mut escaped = None;
+/// cell.with_scope(|borrow| escaped = Some(borrow));
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let copied = cell.with_scope(|borrow| *borrow);
+/// assert_eq!(copied, 42);
+/// ```
+pub struct ScopedBorrowCannotEscape;
+
+/// Threads spawned with `spawn_with_borrow` cannot be handed borrows of a value that
+/// is not `Sync`:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let cell = AtomicLendCell::new(Cell::new(1));
+/// cell.scope(|s| {
+///     s.spawn_with_borrow(|borrow| borrow.get());
+/// });
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(1);
+/// cell.scope(|s| {
+///     s.spawn_with_borrow(|borrow| *borrow);
+/// });
+/// ```
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+pub struct ScopeBorrowOfNonSyncIsNotSend;
+
+/// An `ExclusiveBorrow` only requires `T: Send` to be sent, but still rejects values
+/// that are not `Send`:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::rc::Rc;
+///
+/// let mut cell = AtomicLendCell::new(Rc::new(1));
+/// let borrow = cell.borrow_exclusive().unwrap();
+/// std::thread::scope(|s| s.spawn(move || **borrow).join().unwrap());
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let mut cell = AtomicLendCell::new(Cell::new(1));
+/// let borrow = cell.borrow_exclusive().unwrap();
+/// std::thread::scope(|s| s.spawn(move || borrow.get()).join().unwrap());
+/// ```
+pub struct ExclusiveBorrowOfNonSendIsNotSend;
+
+/// An `ExclusiveBorrow` borrows its owner mutably, so a reference taken from the owner
+/// can't be used while the value is lent exclusively:
+///
+/// ```compile_fail,E0502
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let mut cell = AtomicLendCell::new(Cell::new(1));
+/// let local = cell.as_ref();
+/// let borrow = cell.borrow_exclusive().unwrap();
+/// std::thread::scope(|s| s.spawn(move || borrow.set(2)).join().unwrap());
+/// local.set(3);
+/// ```
+pub struct ExclusiveBorrowLocksOwner;
+
+/// Borrows of an `OwnedLendCell` may keep the value alive on another thread, so they
+/// require the value to be `Send` as well as `Sync`:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::owned::OwnedLendCell;
+/// use std::sync::MutexGuard;
+///
+/// fn send_borrow(cell: &OwnedLendCell<MutexGuard<'static, i32>>) {
+///     let borrow = cell.borrow();
+///     std::thread::spawn(move || drop(borrow));
+/// }
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::owned::OwnedLendCell;
+///
+/// let cell = OwnedLendCell::new(1);
+/// let borrow = cell.borrow();
+/// std::thread::spawn(move || drop(borrow)).join().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub struct OwnedBorrowOfNonSendIsNotSend;
+
+/// A `BorrowGuard` can't be sent to another thread, even though the borrow it was
+/// created from can:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// let guard = borrow.as_guard();
+/// std::thread::scope(|s| {
+///     s.spawn(move || *guard);
+/// });
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::scope(|s| {
+///     s.spawn(|| *borrow.as_guard());
+/// });
+/// ```
+#[cfg(not(feature = "no-send"))]
+pub struct BorrowGuardIsNotSend;
+
+/// With the `no-send` feature, borrows of either implementation can't leave the thread
+/// that created them, even for values that are `Sync`:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::scope(|s| {
+///     s.spawn(move || *borrow);
+/// });
+/// ```
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::scope(|s| {
+///     s.spawn(|| *borrow);
+/// });
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::scope(|s| {
+///     s.spawn(|| *cell);
+/// });
+/// assert_eq!(*borrow, 42);
+/// ```
+#[cfg(feature = "no-send")]
+pub struct BorrowIsNotSendWithNoSend;
+
+/// `borrow_sync` refuses values that are not `Sync` where the borrow is created, rather
+/// than where it is sent:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let cell = AtomicLendCell::new(Cell::new(1));
+/// let borrow = cell.borrow_sync();
+/// ```
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+/// use std::cell::RefCell;
+///
+/// let cell = AtomicLendCell::new(RefCell::new(1));
+/// let borrow = cell.borrow_sync();
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+/// use std::cell::Cell;
+///
+/// let cell = AtomicLendCell::new(Cell::new(1));
+/// let borrow = cell.borrow();
+/// borrow.set(2);
+/// assert_eq!(cell.get(), 2);
+/// ```
+pub struct BorrowSyncRequiresSync;
+
+/// The owner of a `GuardedBorrow` can be neither moved nor dropped while the guard is
+/// alive:
+///
+/// ```compile_fail,E0505
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let guard = cell.borrow_guarded();
+/// drop(cell);
+/// assert_eq!(*guard, 42);
+/// ```
+///
+/// ```compile_fail,E0505
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let guard = cell.borrow_guarded();
+/// let moved = cell;
+/// assert_eq!(*guard, 42);
+/// ```
+///
+/// ```
+/// use atomic_lend_cell::atomic_counting::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let guard = cell.borrow_guarded();
+/// assert_eq!(*guard, 42);
+/// drop(guard);
+/// drop(cell);
+/// ```
+pub struct GuardedBorrowPinsOwner;
@@ -0,0 +1,34 @@
+//! # Errors
+//!
+//! The error type reported by fallible borrow operations, such as `try_borrow` in both
+//! implementations.
+
+use core::fmt;
+
+/// The reason a borrow could not be created or used
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BorrowError {
+    /// The owner was dropped while the borrow was outstanding
+    Poisoned,
+    /// The owner has already been dropped
+    OwnerDropped,
+    /// The owner already lends its value as often as its borrow limit allows
+    LimitExceeded,
+    /// The owner has been sealed and no longer issues new borrows
+    Sealed
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowError::Poisoned => f.write_str("the owner was dropped while the value was borrowed"),
+            BorrowError::OwnerDropped => f.write_str("the owner has already been dropped"),
+            BorrowError::LimitExceeded => f.write_str("the owner's borrow limit has been reached"),
+            BorrowError::Sealed => f.write_str("the owner has been sealed against new borrows")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BorrowError {}
@@ -4,39 +4,129 @@
 //! # Atomic Lend Cell
 //! 
 //! A thread-safe container that allows lending references to data across threads
-//! using epoch-based reclamation for safety verification without per-object reference counting.
+//! using a per-cell liveness epoch for safety verification without per-object reference counting.
 //! 
 //! This module provides two main types:
 //! - `AtomicLendCell<T>`: The owner that contains the data and can lend it out
 //! - `AtomicBorrowCell<T>`: A lightweight borrow of data that can be freely sent between threads
 //!
-//! Unlike atomic reference counting, this implementation uses a single boolean flag
+//! Unlike atomic reference counting, this implementation uses a single atomic word
 //! to track the owner's lifetime, reducing synchronization overhead while still
-//! ensuring safety.
+//! ensuring safety. The word holds an epoch that is unique to each cell while it is
+//! alive, so a borrow also notices when its owner's memory is reused by a new cell.
+//!
+//! Note that this is not epoch-based reclamation in the sense of deferred freeing: the
+//! value is dropped as soon as its owner is, and the epoch only lets borrows detect
+//! that. Reading through a borrow after its owner has been dropped is still undefined
+//! behavior unless it goes through a checked accessor such as `checked_as_ref`.
 
-use std::{ops::Deref, sync::atomic::{AtomicBool, Ordering}};
+use crate::{BorrowError, BorrowGuard, ScopedBorrow, padded::Padded, traits::{BorrowHandle, LendCell}};
+use core::{cell::UnsafeCell, fmt, hash::{Hash, Hasher}, mem::{self, ManuallyDrop}, ops::Deref, ptr::{self, NonNull}, sync::atomic::{AtomicUsize, Ordering}};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+#[cfg(feature = "std")]
+use crate::scoped::LendScope;
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::BuildHasher, ops::Range, sync::{Arc, atomic::AtomicBool}};
 
-/// A container that allows thread-safe lending of its contained value using epoch-based reclamation
+/// A container that allows thread-safe lending of its contained value, tracking its lifetime with an epoch
 ///
-/// `AtomicLendCell<T>` owns a value of type `T` and maintains an atomic boolean
+/// `AtomicLendCell<T>` owns a value of type `T` and maintains an atomic epoch
 /// to track its lifetime. It ensures that the value isn't accessed after being dropped,
 /// with validation occurring in debug builds.
-pub struct AtomicLendCell<T> {
-    data: T,
-    is_alive: AtomicBool
+///
+/// The contained value may be unsized, so that e.g. a `Box<AtomicLendCell<[T; N]>>` can be
+/// coerced to a `Box<AtomicLendCell<[T]>>` and lend slice borrows.
+///
+/// Zero-sized values such as `()` are supported as well. Nothing is ever read through
+/// their borrows, but their liveness is tracked like any other, so `checked_as_ref`
+/// still reports a dropped owner.
+///
+/// # Moving the cell
+///
+/// Borrows point at the epoch and the value inside the cell, so the cell must stay where
+/// it is while it is borrowed. The value never moves within the cell, so `as_ptr` returns
+/// the same address for as long as the cell stays in place, and borrows taken at different
+/// times all share it. Moving the cell while borrows exist, however, leaves them pointing at
+/// the old location, and the compiler can't catch this because borrows don't hold a
+/// lifetime. Pinning the cell to the stack with `pin!`, or keeping it in a `Box` (or an
+/// `Arc`), rules the move out:
+///
+/// ```
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+/// use std::pin::pin;
+///
+/// // The original binding is consumed, so the cell can no longer be moved
+/// let cell = pin!(AtomicLendCell::new(42));
+/// let borrow = cell.borrow();
+/// assert_eq!(borrow.as_ptr(), cell.as_ptr());
+///
+/// // Moving the box moves only the pointer, not the cell
+/// let boxed = Box::new(AtomicLendCell::new(42));
+/// let borrow = boxed.borrow();
+/// let moved = boxed;
+/// assert_eq!(borrow.as_ptr(), moved.as_ptr());
+/// drop(borrow);
+/// ```
+pub struct AtomicLendCell<T: ?Sized> {
+    epoch: Padded<AtomicUsize>,
+    /// Bumped whenever the value may have changed, for `StampedBorrow::is_current`
+    #[cfg(target_has_atomic = "64")]
+    generation: AtomicU64,
+    data: UnsafeCell<T>
 }
 
-impl<T> AtomicLendCell<T> {
+impl<T: ?Sized> AtomicLendCell<T> {
     /// Returns a reference to the contained value
     ///
     /// This method provides direct access to the value inside the cell without
     /// creating a borrowing relationship.
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &T {
-        &self.data
+        unsafe { &*self.data.get() }
+    }
+
+    /// Returns a raw pointer to the contained value
+    ///
+    /// The pointer is the same one that borrows of this cell hold. Dereferencing it after
+    /// the cell has been dropped or moved is undefined behavior.
+    pub fn as_ptr(&self) -> *const T {
+        self.data.get()
+    }
+
+    /// Returns a mutable reference to the contained value
+    ///
+    /// Unlike the reference-counting implementation, this cell does not track its
+    /// outstanding borrows, so this always returns `Some`. The caller must ensure
+    /// that no `AtomicBorrowCell` is reading the value while it is being mutated.
+    /// Since the value may be changed through the reference, this bumps the cell's
+    /// generation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(42);
+    /// *cell.get_mut().unwrap() += 1;
+    ///
+    /// assert_eq!(*cell, 43);
+    /// ```
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // The value may be changed through the reference, as if it had been replaced
+        self.bump_generation();
+        Some(self.data.get_mut())
     }
 }
 
-impl<T> Deref for AtomicLendCell<T> {
+impl<T: ?Sized> AtomicLendCell<T> {
+    /// Returns the reference to this cell's epoch handed to borrows
+    fn epoch_ref(&self) -> EpochRef {
+        EpochRef::new(NonNull::from(&*self.epoch))
+    }
+}
+
+impl<T: ?Sized> Deref for AtomicLendCell<T> {
     type Target = T;
     /// Dereferences to the contained value
     ///
@@ -46,49 +136,492 @@ impl<T> Deref for AtomicLendCell<T> {
     }
 }
 
-impl<T> Drop for AtomicLendCell<T> {
+impl<T: ?Sized> Drop for AtomicLendCell<T> {
     /// Marks the cell as no longer alive when it's dropped
     ///
     /// This allows borrows to detect if they're being used after the owner was dropped.
     fn drop(&mut self) {
         // Mark as no longer alive
-        self.is_alive.store(false, Ordering::Release);
+        self.epoch.store(DEAD, Ordering::Release);
         
         // Optional: Give in-flight operations a chance to complete
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, feature = "std"))]
         std::thread::yield_now();
     }
 }
 
+/// The state of an `AtomicLendCell` as returned by [`AtomicLendCell::state`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellState {
+    /// The cell can lend its value
+    Alive,
+    /// The cell's destructor has run
+    Dropped
+}
+
+/// The epoch stored in a cell once it has been dropped
+const DEAD: usize = 0;
+
+/// The epoch stored in a new cell until it is first borrowed
+///
+/// Taking the epoch on the first borrow rather than on construction keeps `new` a
+/// `const fn`.
+const UNASSIGNED: usize = usize::MAX;
+
+/// The bit set in the epoch of a cell whose borrows are revoked
+///
+/// Epochs never have it set, so the borrows of a revoked cell no longer match its epoch
+/// until the bit is cleared again.
+const REVOKED: usize = 1 << (usize::BITS - 1);
+
+/// The epoch taken by the next cell
+static NEXT_EPOCH: AtomicUsize = AtomicUsize::new(DEAD + 1);
+
+/// Returns an epoch that no live cell holds
+///
+/// Epochs only repeat after the counter wraps around, which takes long enough for a
+/// stale borrow to have been noticed by then.
+fn next_epoch() -> usize {
+    loop {
+        let epoch = NEXT_EPOCH.fetch_add(1, Ordering::Relaxed) & !REVOKED;
+        if epoch != DEAD {
+            return epoch;
+        }
+    }
+}
+
+/// A borrow's reference to the epoch of the cell that issued it
+#[derive(Clone, Copy)]
+struct EpochRef {
+    epoch_ptr: NonNull<AtomicUsize>,
+    epoch: usize
+}
+
+impl EpochRef {
+    /// Takes the current epoch of a live cell, assigning one on its first borrow
+    ///
+    /// Borrows of a revoked cell take its epoch without the `REVOKED` bit, so they
+    /// become usable once the cell is reinstated.
+    fn new(epoch_ptr: NonNull<AtomicUsize>) -> Self {
+        let current = unsafe { epoch_ptr.as_ref() };
+        let mut epoch = current.load(Ordering::Relaxed);
+        if epoch == UNASSIGNED {
+            let fresh = next_epoch();
+            // Another thread may be borrowing the same cell for the first time
+            epoch = match current.compare_exchange(UNASSIGNED, fresh, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => fresh,
+                Err(assigned) => assigned
+            };
+        }
+        Self { epoch_ptr, epoch: epoch & !REVOKED }
+    }
+
+    /// Returns `true` if the cell that issued the borrow is still alive and hasn't
+    /// revoked its borrows
+    ///
+    /// A different cell constructed at the same address holds a different epoch, so
+    /// it is not mistaken for the original owner.
+    fn is_alive(&self) -> bool {
+        let current = unsafe { self.epoch_ptr.as_ref() }.load(Ordering::Acquire);
+        current == self.epoch && current != DEAD
+    }
+
+    /// Returns `true` if the cell that issued the borrow is still alive, even if it has
+    /// revoked its borrows
+    ///
+    /// Used by the debug checks, which guard against reading a dropped value rather
+    /// than enforcing revocation.
+    #[cfg(debug_assertions)]
+    fn is_owner_alive(&self) -> bool {
+        let current = unsafe { self.epoch_ptr.as_ref() }.load(Ordering::Acquire) & !REVOKED;
+        current == self.epoch && current != DEAD
+    }
+}
+
 /// A thread-safe reference to data contained in an `AtomicLendCell`
 ///
 /// `AtomicBorrowCell<T>` holds a pointer to data in an `AtomicLendCell<T>` and
 /// checks the lender's liveness in debug builds. It can be safely sent between threads.
-pub struct AtomicBorrowCell<T> {
-    data_ptr: *const T,
-    owner_alive_ptr: *const AtomicBool
+pub struct AtomicBorrowCell<T: ?Sized> {
+    data_ptr: NonNull<T>,
+    owner: EpochRef
 }
 
-impl<T> AtomicBorrowCell<T> {
+impl<T: ?Sized> AtomicBorrowCell<T> {
     /// Returns a reference to the borrowed value
     ///
     /// This method provides access to the value inside the original `AtomicLendCell`.
     /// In debug builds, it verifies that the owner is still alive.
+    ///
+    /// In release builds no check is performed, and calling this after the owner has
+    /// been dropped reads freed memory. Use `checked_as_ref` where that cannot be ruled out.
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &T {
         #[cfg(debug_assertions)]
         {
-            let is_alive = unsafe { self.owner_alive_ptr.as_ref().unwrap() }
-                .load(Ordering::Acquire);
+            let is_alive = self.owner.is_owner_alive();
             if !is_alive {
-                panic!("Attempting to access AtomicBorrowCell after owner was dropped");
+                panic!("Attempting to access AtomicBorrowCell<{}> after owner was dropped", core::any::type_name::<T>());
             }
         }
         
-        unsafe { self.data_ptr.as_ref().unwrap() }
+        unsafe { self.data_ptr.as_ref() }
+    }
+
+    /// Calls `f` with a reference to the borrowed value and returns its result
+    ///
+    /// The debug liveness check is done once for the whole call instead of on every
+    /// dereference, which makes this convenient for doing several operations on the
+    /// value, e.g. atomic operations on an `AtomicLendCell<AtomicUsize>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let cell = AtomicLendCell::new(AtomicUsize::new(0));
+    /// let borrow = cell.borrow();
+    /// let previous = borrow.with(|counter| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    ///     counter.fetch_add(1, Ordering::Relaxed)
+    /// });
+    /// assert_eq!(previous, 1);
+    /// ```
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(self.as_ref())
+    }
+
+    /// Returns a guard that dereferences to the borrowed value
+    ///
+    /// The guard borrows this `AtomicBorrowCell`, so the borrow can't be dropped or
+    /// moved to another thread while the guard is in use, and the guard itself can't
+    /// be sent anywhere either. This gives back compile-time checking for code that
+    /// only uses the value locally. The debug liveness check is
+    /// done once, when the guard is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let borrow = cell.borrow();
+    /// let guard = borrow.as_guard();
+    ///
+    /// assert_eq!(guard.len() + guard[0], 4);
+    /// ```
+    pub fn as_guard(&self) -> BorrowGuard<'_, T> {
+        BorrowGuard::new(self.as_ref())
+    }
+
+    /// Converts the borrow into a `ReadView` that can only be read through
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(String::from("config"));
+    /// let view = cell.borrow().read_view();
+    ///
+    /// assert_eq!(view.len(), 6);
+    /// ```
+    pub fn read_view(self) -> ReadView<T> {
+        ReadView(self)
+    }
+
+    /// Returns the raw pointer to the borrowed value
+    ///
+    /// The pointer is returned without dereferencing it or checking that the owner is
+    /// still alive, e.g. for passing it across an FFI boundary.
+    ///
+    /// **Dereferencing the pointer after the owner has been dropped is undefined behavior.**
+    pub fn as_ptr(&self) -> *const T {
+        self.data_ptr.as_ptr()
+    }
+
+    /// Returns `true` if both borrows point at the same value
+    ///
+    /// Like `Arc::ptr_eq`, this compares addresses rather than values, and never reads
+    /// the values, so it may be called even after the owner has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::{AtomicBorrowCell, AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let other = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert!(AtomicBorrowCell::ptr_eq(&borrow, &borrow.clone()));
+    /// assert!(!AtomicBorrowCell::ptr_eq(&borrow, &other.borrow()));
+    /// ```
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        ptr::addr_eq(a.data_ptr.as_ptr(), b.data_ptr.as_ptr())
+    }
+
+    /// Returns `true` if both borrows were issued by the same cell
+    ///
+    /// Unlike `ptr_eq`, this compares the owner's liveness epoch rather than the borrowed
+    /// values, so borrows projected onto different parts of one value share an owner.
+    /// It never reads the values or the owner, so it may be called even after the
+    /// owner has been dropped.
+    ///
+    /// A borrow of a dropped cell is never reported to share its owner with a borrow of
+    /// a later cell at the same address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::{AtomicBorrowCell, AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new((1, 2));
+    /// let other = AtomicLendCell::new((1, 2));
+    /// let first = cell.borrow().map(|pair| &pair.0);
+    /// let second = cell.borrow().map(|pair| &pair.1);
+    ///
+    /// assert!(AtomicBorrowCell::same_owner(&first, &second));
+    /// assert!(!AtomicBorrowCell::same_owner(&first, &other.borrow()));
+    /// ```
+    pub fn same_owner<U: ?Sized>(a: &Self, b: &AtomicBorrowCell<U>) -> bool {
+        a.owner.epoch_ptr == b.owner.epoch_ptr && a.owner.epoch == b.owner.epoch
+    }
+
+    /// Returns a reference to the borrowed value if the owner is still alive
+    ///
+    /// Unlike `as_ref`, the liveness check is performed in every build profile. The
+    /// check and the access are a single `Acquire` load of the owner's epoch, so there
+    /// is no window between asking whether the owner is alive and taking the reference
+    /// in which the answer could go stale unnoticed.
+    ///
+    /// Nothing stops the owner from being dropped right after that load, though. The
+    /// returned reference is only valid for as long as the owner is kept alive by other
+    /// means, so this is a best-effort check for a value that is expected to outlive its
+    /// readers, not a replacement for keeping the owner alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(borrow.checked_as_ref(), Some(&42));
+    /// ```
+    pub fn checked_as_ref(&self) -> Option<&T> {
+        let is_alive = self.owner.is_alive();
+        if is_alive {
+            Some(unsafe { self.data_ptr.as_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Calls `f` with the borrowed value if the owner is still alive, or returns
+    /// `default()` otherwise
+    ///
+    /// Like `checked_as_ref`, the liveness check is performed in every build profile,
+    /// which makes this suitable for falling back to a default once a shared value has
+    /// gone away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(8080);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(borrow.read_or(|| 80, |port| *port), 8080);
+    /// ```
+    pub fn read_or<R>(&self, default: impl FnOnce() -> R, f: impl FnOnce(&T) -> R) -> R {
+        match self.checked_as_ref() {
+            Some(data) => f(data),
+            None => default()
+        }
+    }
+
+    /// Clones the borrow only if the owner is still alive
+    ///
+    /// This performs an `Acquire` load of the owner's epoch and returns `None` if the
+    /// owner has already been dropped, so no new borrow of dropped data is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(*borrow.try_clone().unwrap(), 42);
+    /// ```
+    pub fn try_clone(&self) -> Option<Self> {
+        if self.owner.is_alive() {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Projects the borrow onto a part of the borrowed value
+    ///
+    /// The returned borrow checks the same liveness flag as this one. The closure can
+    /// only return references into its argument or to `'static` data, so the projection
+    /// stays valid for as long as the original borrow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new((1, String::from("hello")));
+    /// let borrow = cell.borrow().map(|pair| &pair.1);
+    ///
+    /// assert_eq!(*borrow, "hello");
+    /// ```
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(self, f: F) -> AtomicBorrowCell<U> {
+        AtomicBorrowCell {
+            data_ptr: NonNull::from(f(self.as_ref())),
+            owner: self.owner
+        }
+    }
+
+    /// Projects the borrow onto a part of the borrowed value if the owner is still alive
+    ///
+    /// Like `checked_as_ref`, the liveness check is performed in every build profile, so
+    /// the closure never sees a dropped value. Once the owner has been dropped, the
+    /// borrow is discarded without the usual debug-build panic and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new((1, String::from("hello")));
+    /// let borrow = cell.borrow().try_map(|pair| &pair.1).unwrap();
+    ///
+    /// assert_eq!(*borrow, "hello");
+    /// ```
+    pub fn try_map<U: ?Sized, F: FnOnce(&T) -> &U>(self, f: F) -> Option<AtomicBorrowCell<U>> {
+        match self.checked_as_ref() {
+            Some(data) => Some(AtomicBorrowCell {data_ptr: NonNull::from(f(data)), owner: self.owner}),
+            None => {
+                // The owner is gone, so there is nothing left for the borrow to check
+                mem::forget(self);
+                None
+            }
+        }
+    }
+
+    /// Projects the borrow onto a part of the borrowed value that may not exist
+    ///
+    /// If the closure returns `None`, the original borrow is handed back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(Ok::<i32, String>(42));
+    /// let borrow = cell.borrow().filter_map(|value| value.as_ref().err()).unwrap_err();
+    /// let borrow = borrow.filter_map(|value| value.as_ref().ok()).unwrap();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn filter_map<U: ?Sized, F: FnOnce(&T) -> Option<&U>>(self, f: F) -> Result<AtomicBorrowCell<U>, Self> {
+        match f(self.as_ref()).map(NonNull::from) {
+            Some(data_ptr) => Ok(AtomicBorrowCell {data_ptr, owner: self.owner}),
+            None => Err(self)
+        }
+    }
+
+    /// Splits the borrow into borrows of two parts of the borrowed value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new((1, String::from("hello")));
+    /// let (number, text) = cell.borrow().split(|pair| (&pair.0, &pair.1));
+    ///
+    /// assert_eq!((*number, text.as_str()), (1, "hello"));
+    /// ```
+    pub fn split<U: ?Sized, V: ?Sized, F: FnOnce(&T) -> (&U, &V)>(self, f: F) -> (AtomicBorrowCell<U>, AtomicBorrowCell<V>) {
+        let (first, second) = f(self.as_ref());
+        (
+            AtomicBorrowCell {data_ptr: NonNull::from(first), owner: self.owner},
+            AtomicBorrowCell {data_ptr: NonNull::from(second), owner: self.owner}
+        )
+    }
+
+    /// Creates a weak borrow of the same value
+    ///
+    /// Unlike `AtomicBorrowCell`, the weak borrow may outlive the owner and has to be
+    /// upgraded before the value can be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let weak = cell.borrow().downgrade();
+    ///
+    /// assert_eq!(*weak.upgrade().unwrap(), 42);
+    /// ```
+    pub fn downgrade(&self) -> AtomicWeakBorrow<T> {
+        AtomicWeakBorrow {
+            data_ptr: self.data_ptr,
+            owner: self.owner
+        }
+    }
+
+    /// Consumes the borrow, returning the pointers to the value and to the owner's epoch
+    ///
+    /// The borrow can be reconstructed with [`from_raw`](Self::from_raw).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::{AtomicBorrowCell, AtomicLendCell};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let (data, alive) = cell.borrow().into_raw();
+    ///
+    /// let borrow = unsafe { AtomicBorrowCell::from_raw(data, alive) };
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn into_raw(self) -> (*const T, *const AtomicUsize) {
+        let raw = (self.data_ptr.as_ptr().cast_const(), self.owner.epoch_ptr.as_ptr().cast_const());
+        // Skips the liveness check on drop, the pointers may outlive the owner
+        mem::forget(self);
+        raw
+    }
+
+    /// Reconstructs a borrow from the pointers returned by [`into_raw`](Self::into_raw)
+    ///
+    /// # Safety
+    ///
+    /// The pointers must come from a call to `into_raw`, and the owner must not have
+    /// been dropped or moved since. The borrow takes the owner's current epoch, so
+    /// this is also what makes it detect a later drop of the owner.
+    pub unsafe fn from_raw(data: *const T, epoch: *const AtomicUsize) -> Self {
+        // Neither pointer can be null, since they come from `into_raw`
+        unsafe {
+            AtomicBorrowCell {
+                data_ptr: NonNull::new_unchecked(data.cast_mut()),
+                owner: EpochRef::new(NonNull::new_unchecked(epoch.cast_mut()))
+            }
+        }
     }
 }
 
-impl<T> Deref for AtomicBorrowCell<T> {
+impl<T: ?Sized> Deref for AtomicBorrowCell<T> {
     type Target = T;
     /// Dereferences to the borrowed value
     ///
@@ -98,27 +631,41 @@ impl<T> Deref for AtomicBorrowCell<T> {
     }
 }
 
-impl<T> Drop for AtomicBorrowCell<T> {
+impl<T: ?Sized> Drop for AtomicBorrowCell<T> {
     /// Checks if the owner is still alive when this borrow is dropped
     ///
     /// In debug builds, this will panic if the borrow is dropped after the owner,
-    /// helping to detect potential use-after-free bugs.
+    /// helping to detect potential use-after-free bugs. If the thread is already
+    /// panicking, the violation is only reported on stderr to avoid an abort.
     fn drop(&mut self) {
         #[cfg(debug_assertions)]
         {
-            let is_alive = unsafe { self.owner_alive_ptr.as_ref().unwrap() }
-                .load(Ordering::Acquire);
+            let is_alive = self.owner.is_owner_alive();
             if !is_alive {
+                // A second panic while unwinding would abort the process and hide the
+                // original panic, so only report the violation in that case
+                #[cfg(feature = "std")]
+                if std::thread::panicking() {
+                    std::eprintln!("AtomicBorrowCell<{}> dropped after its owner was dropped", core::any::type_name::<T>());
+                    return;
+                }
                 // We were dropped after owner - this shouldn't happen in correct code
-                panic!("AtomicBorrowCell dropped after its owner was dropped");
+                panic!("AtomicBorrowCell<{}> dropped after its owner was dropped", core::any::type_name::<T>());
             }
         }
     }
 }
 
-// These trait implementations make `AtomicBorrowCell` safe to send between threads
-unsafe impl<T: Sync> Send for AtomicBorrowCell<T> {}
-unsafe impl<T: Sync> Sync for AtomicBorrowCell<T> {}
+// The value is only replaced through `&mut self`, so sharing the cell only
+// requires sharing the value
+unsafe impl<T: ?Sized + Sync> Sync for AtomicLendCell<T> {}
+
+// These trait implementations make `AtomicBorrowCell` safe to send between threads,
+// unless the `no-send` feature keeps borrows on the thread that created them
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Send for AtomicBorrowCell<T> {}
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Sync for AtomicBorrowCell<T> {}
 
 impl<T> AtomicLendCell<T> {
     /// Creates a new `AtomicLendCell` containing the given value
@@ -130,62 +677,1328 @@ impl<T> AtomicLendCell<T> {
     ///
     /// let cell = AtomicLendCell::new(42);
     /// ```
-    pub fn new(data: T) -> Self {
-        Self { data, is_alive: AtomicBool::new(true) }
+    ///
+    /// This is a `const fn`, so cells can be placed in statics:
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// static CONFIG: AtomicLendCell<u32> = AtomicLendCell::new(7);
+    ///
+    /// assert_eq!(*CONFIG.borrow(), 7);
+    /// ```
+    pub const fn new(data: T) -> Self {
+        Self {
+            epoch: Padded::new(AtomicUsize::new(UNASSIGNED)),
+            #[cfg(target_has_atomic = "64")]
+            generation: AtomicU64::new(0),
+            data: UnsafeCell::new(data)
+        }
     }
 
-    /// Creates a new `AtomicBorrowCell` for the contained value
+    /// Consumes the cell and returns the contained value
     ///
-    /// This returns a borrow that can be sent to other threads. The borrow will
-    /// verify the owner's liveness in debug builds.
+    /// Moving the cell into this method already invalidates any outstanding borrows:
+    /// their pointers still refer to the cell's old location, which is no longer kept
+    /// up to date, so they must not be used or checked afterwards.
     ///
     /// # Examples
     ///
     /// ```
     /// use atomic_lend_cell::AtomicLendCell;
     ///
-    /// let cell = AtomicLendCell::new(42);
-    /// let borrow = cell.borrow();
+    /// let cell = AtomicLendCell::new(String::from("hello"));
     ///
-    /// assert_eq!(*borrow, 42);
+    /// assert_eq!(cell.into_inner(), "hello");
     /// ```
-    pub fn borrow(&self) -> AtomicBorrowCell<T> {
-        AtomicBorrowCell {
-            data_ptr: (&self.data) as *const T,
-            owner_alive_ptr: &self.is_alive as *const AtomicBool
-        }
+    pub fn into_inner(self) -> T {
+        // Suppress the destructor so the data is moved out exactly once
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.data) }.into_inner()
     }
-    
-}
 
-impl<'a, T> AtomicLendCell<&'a T> {
-    /// Creates a new `AtomicBorrowCell` that borrows the referenced value directly
+    /// Consumes the cell and returns a new cell containing the transformed value
     ///
-    /// This is useful when the `AtomicLendCell` contains a reference, and you want to
-    /// borrow the underlying value rather than the reference itself.
-    pub fn borrow_deref(&'a self) -> AtomicBorrowCell<T> {
-        AtomicBorrowCell {
-            data_ptr: self.data as *const T,
-            owner_alive_ptr: &self.is_alive as *const AtomicBool
-        }
-    }
-}
-
-impl<T> Clone for AtomicBorrowCell<T> {
-    /// Creates a new `AtomicBorrowCell` that borrows the same value
+    /// The value is moved out as with `into_inner` and passed to `f`. The new cell gets
+    /// a fresh epoch once it is borrowed.
     ///
-    /// Unlike reference counting, this doesn't need to increment any counters,
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(7u32);
+    /// let cell = cell.map_owner(|n| format!("#{n}"));
+    ///
+    /// assert_eq!(*cell.borrow(), "#7");
+    /// ```
+    pub fn map_owner<U>(self, f: impl FnOnce(T) -> U) -> AtomicLendCell<U> {
+        AtomicLendCell::new(f(self.into_inner()))
+    }
+
+    /// Replaces the contained value, returning the old one
+    ///
+    /// The value stays at the same address, so existing borrows read the new value
+    /// afterwards. Taking `&mut self` rules out references obtained through `as_ref`,
+    /// `Deref` or a scope, but not borrows, which don't hold a lifetime.
+    ///
+    /// # Safety
+    ///
+    /// This cell does not track its borrows, so the caller must ensure that no borrow
+    /// reads the value while it is being replaced, and that no reference obtained from a
+    /// borrow before the call is used afterwards, since it may point into the old value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(1);
+    /// // The cell has not been borrowed yet
+    /// assert_eq!(unsafe { cell.replace(2) }, 1);
+    ///
+    /// assert_eq!(*cell.borrow(), 2);
+    /// ```
+    pub unsafe fn replace(&mut self, value: T) -> T {
+        let old = mem::replace(self.data.get_mut(), value);
+        self.bump_generation();
+        old
+    }
+
+    /// Swaps the contained values of two cells
+    ///
+    /// Existing borrows of either cell read the swapped-in value afterwards.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`replace`](Self::replace), for the borrows of both cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let mut front = AtomicLendCell::new(1);
+    /// let mut back = AtomicLendCell::new(2);
+    /// // Neither cell has been borrowed yet
+    /// unsafe { front.swap(&mut back) };
+    ///
+    /// assert_eq!((*front, *back), (2, 1));
+    /// ```
+    pub unsafe fn swap(&mut self, other: &mut Self) {
+        mem::swap(self.data.get_mut(), other.data.get_mut());
+        self.bump_generation();
+        other.bump_generation();
+    }
+
+    /// Takes the contained value, leaving `T::default()` in its place
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`replace`](Self::replace).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// // The cell has not been borrowed yet
+    /// assert_eq!(unsafe { cell.take() }, vec![1, 2, 3]);
+    ///
+    /// assert!(cell.borrow().is_empty());
+    /// ```
+    pub unsafe fn take(&mut self) -> T
+    where
+        T: Default
+    {
+        unsafe { self.replace(T::default()) }
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<T> {
+    /// Creates a new `AtomicBorrowCell` for the contained value
+    ///
+    /// This returns a borrow that can be sent to other threads if `T` is `Sync`. The
+    /// borrow will verify the owner's liveness in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn borrow(&self) -> AtomicBorrowCell<T> {
+        AtomicBorrowCell {
+            // `UnsafeCell::get` never returns null
+            data_ptr: unsafe { NonNull::new_unchecked(self.data.get()) },
+            owner: self.epoch_ref()
+        }
+    }
+
+    /// Creates a new `AtomicBorrowCell` that is known to be sendable
+    ///
+    /// This is `borrow` with a `T: Sync` bound, for borrows that are meant for other
+    /// threads. A borrow of a value that isn't `Sync` can't be sent anyway, but with
+    /// `borrow` that is only reported where the borrow is sent, while this reports it
+    /// where the borrow is created. With the `no-send` feature no borrow is sendable,
+    /// whatever the bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let borrow = cell.borrow_sync();
+    /// let sum = std::thread::scope(|s| s.spawn(move || borrow.iter().sum::<i32>()).join().unwrap());
+    ///
+    /// assert_eq!(sum, 6);
+    /// # }
+    /// ```
+    pub fn borrow_sync(&self) -> AtomicBorrowCell<T>
+    where
+        T: Sync
+    {
+        self.borrow()
+    }
+
+    /// Creates a new `AtomicBorrowCell` only if the cell is still alive
+    ///
+    /// This performs an `Acquire` load of the liveness flag and returns
+    /// `BorrowError::OwnerDropped` if the owner has already been marked as dropped,
+    /// allowing callers to degrade gracefully instead of panicking later in `as_ref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.try_borrow().unwrap();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn try_borrow(&self) -> Result<AtomicBorrowCell<T>, BorrowError> {
+        if self.epoch.load(Ordering::Acquire) != DEAD {
+            Ok(self.borrow())
+        } else {
+            Err(BorrowError::OwnerDropped)
+        }
+    }
+
+    /// Returns a snapshot of the cell's state for diagnostics
+    ///
+    /// This cell does not track its borrows, so only its liveness is reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::{AtomicLendCell, CellState};
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// assert_eq!(cell.state(), CellState::Alive);
+    /// ```
+    pub fn state(&self) -> CellState {
+        if self.epoch.load(Ordering::Acquire) != DEAD {
+            CellState::Alive
+        } else {
+            CellState::Dropped
+        }
+    }
+
+    /// Revokes all borrows of the cell until it is reinstated
+    ///
+    /// The cell itself stays alive and keeps issuing borrows, but checked accesses
+    /// through any of its borrows, such as `checked_as_ref`, `try_clone` or
+    /// `AtomicWeakBorrow::upgrade`, fail until [`reinstate`](Self::reinstate) is called.
+    /// This suits capability revocation, where access to a value has to be withdrawn
+    /// while its owner lives on.
+    ///
+    /// Revocation is only enforced by the checked accessors. `as_ref` and `Deref` still
+    /// read the value, as it remains valid, so revocable borrows should only be read
+    /// through `checked_as_ref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    ///
+    /// cell.revoke();
+    /// assert_eq!(borrow.checked_as_ref(), None);
+    ///
+    /// cell.reinstate();
+    /// assert_eq!(borrow.checked_as_ref(), Some(&42));
+    /// ```
+    pub fn revoke(&self) {
+        // A cell that was never borrowed needs an epoch to mark
+        self.epoch_ref();
+        self.epoch.fetch_or(REVOKED, Ordering::Release);
+    }
+
+    /// Lets the borrows of a revoked cell access the value again
+    ///
+    /// This has no effect on a cell that isn't revoked, including one that was never
+    /// borrowed.
+    pub fn reinstate(&self) {
+        // Clearing the bit of `UNASSIGNED` would give a cell that was never borrowed a
+        // fixed epoch, shared with every other such cell, so that one is left alone
+        let _ = self.epoch.fetch_update(Ordering::Release, Ordering::Relaxed, |epoch| {
+            (epoch != UNASSIGNED).then_some(epoch & !REVOKED)
+        });
+    }
+
+    /// Returns `true` if the cell's borrows are revoked
+    pub fn is_revoked(&self) -> bool {
+        let epoch = self.epoch.load(Ordering::Acquire);
+        epoch != UNASSIGNED && epoch & REVOKED != 0
+    }
+
+    /// Returns the cell's generation, the number of times its value may have changed
+    ///
+    /// The generation starts at `0` and is bumped by `replace`, `swap`, `take` and
+    /// `get_mut`. These all take `&mut self`, so the generation can't change while a
+    /// reference obtained from the owner is in use.
+    #[cfg(target_has_atomic = "64")]
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Creates a new `StampedBorrow` carrying the cell's current generation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let mut cell = AtomicLendCell::new(1);
+    /// let borrow = cell.borrow_stamped();
+    /// assert!(borrow.is_current());
+    ///
+    /// // The borrow isn't read while the value is replaced
+    /// unsafe { cell.replace(2) };
+    /// assert!(!borrow.is_current());
+    /// assert_eq!(cell.borrow_stamped().generation(), 1);
+    /// ```
+    #[cfg(target_has_atomic = "64")]
+    pub fn borrow_stamped(&self) -> StampedBorrow<T> {
+        StampedBorrow {
+            generation: self.generation(),
+            generation_ptr: NonNull::from(&self.generation),
+            borrow: self.borrow()
+        }
+    }
+
+    /// Records that the value may have changed
+    fn bump_generation(&mut self) {
+        #[cfg(target_has_atomic = "64")]
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns the atomic holding the cell's liveness epoch, for custom handshakes
+    ///
+    /// The epoch is `0` once the cell has been dropped and `usize::MAX` until it is
+    /// first borrowed; any other value identifies this cell to its borrows, which are
+    /// alive only while the epoch still equals the one they were created with. Storing
+    /// `0` therefore invalidates every outstanding borrow, and storing back an epoch
+    /// previously loaded from this cell restores them. The highest bit is set while the
+    /// cell is revoked with [`revoke`](Self::revoke).
+    ///
+    /// Only available with the `internals` feature.
+    ///
+    /// # Safety
+    ///
+    /// Callers must only store `0` or values previously loaded from this same atomic.
+    /// Any other value could match the epoch of a borrow issued by an earlier cell at
+    /// the same address, letting it read this cell's value as its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let borrow = cell.borrow();
+    /// let liveness = unsafe { cell.liveness() };
+    ///
+    /// // Revoke the outstanding borrows, unless someone else got there first
+    /// let epoch = liveness.load(Ordering::Acquire);
+    /// assert!(liveness.compare_exchange(epoch, 0, Ordering::AcqRel, Ordering::Acquire).is_ok());
+    /// assert_eq!(borrow.checked_as_ref(), None);
+    ///
+    /// // Hand them back
+    /// assert!(liveness.compare_exchange(0, epoch, Ordering::AcqRel, Ordering::Acquire).is_ok());
+    /// assert_eq!(borrow.checked_as_ref(), Some(&42));
+    /// ```
+    #[cfg(feature = "internals")]
+    pub unsafe fn liveness(&self) -> &AtomicUsize {
+        &self.epoch
+    }
+
+    /// Lends the contained value to a closure as a `ScopedBorrow`
+    ///
+    /// The borrow is tied to the closure by its lifetime, so the compiler guarantees that
+    /// it does not outlive the cell and no liveness checks are needed. Use this when the value only needs
+    /// to be shared with scoped threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(42);
+    /// let doubled = cell.with_scope(|borrow| *borrow * 2);
+    ///
+    /// assert_eq!(doubled, 84);
+    /// ```
+    pub fn with_scope<F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(ScopedBorrow<'scope, T>) -> R
+    {
+        f(ScopedBorrow::new(self.as_ref()))
+    }
+
+    /// Runs `f` in a `std::thread::scope` that spawns threads with borrows of this cell
+    ///
+    /// This is the one-call form of the most common correct usage: every thread spawned
+    /// through the `LendScope` is joined before this returns, so no borrow outlives
+    /// the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no-send"))] {
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let sums: Vec<i32> = cell.scope(|s| {
+    ///     let workers: Vec<_> = (0..3)
+    ///         .map(|i| s.spawn_with_borrow(move |borrow| borrow[i] * 10))
+    ///         .collect();
+    ///     workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    /// });
+    ///
+    /// assert_eq!(sums, [10, 20, 30]);
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn scope<'env, F, R>(&'env self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&LendScope<'scope, 'env, Self>) -> R
+    {
+        std::thread::scope(|s| f(&LendScope::new(s, self)))
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<&T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the referenced value directly
+    ///
+    /// This is useful when the `AtomicLendCell` contains a reference, and you want to
+    /// borrow the underlying value rather than the reference itself.
+    ///
+    /// The borrow checks the liveness of the cell rather than of the referenced value.
+    /// This is sound because the cell holds a reference to the value, so the value
+    /// outlives the cell and thereby every access the liveness check lets through. The
+    /// cell only has to be borrowed for the call, not for the lifetime of the reference.
+    pub fn borrow_deref(&self) -> AtomicBorrowCell<T> {
+        AtomicBorrowCell {
+            data_ptr: NonNull::from(unsafe { *self.data.get() }),
+            owner: self.epoch_ref()
+        }
+    }
+
+    /// Creates a new `AtomicBorrowCell` of the referenced value only if the cell is still alive
+    ///
+    /// Like `try_borrow`, this returns `None` if the owner has already been marked as
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let value = String::from("hello");
+    /// let cell = AtomicLendCell::new(value.as_str());
+    ///
+    /// assert_eq!(&*cell.try_borrow_deref().unwrap(), "hello");
+    /// ```
+    pub fn try_borrow_deref(&self) -> Option<AtomicBorrowCell<T>> {
+        if self.epoch.load(Ordering::Acquire) != DEAD {
+            Some(self.borrow_deref())
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: ?Sized> AtomicLendCell<AtomicLendCell<T>> {
+    /// Creates a new `AtomicBorrowCell` of the value in the nested cell
+    ///
+    /// The borrow is issued by the outer cell, so it is this cell that has to outlive
+    /// it. The inner cell can't be dropped on its own while the outer one is borrowed,
+    /// so the borrow also keeps the nested value alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow_inner();
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    pub fn borrow_inner(&self) -> AtomicBorrowCell<T> {
+        self.borrow().map(|inner| inner.as_ref())
+    }
+}
+
+impl<T: Clone> AtomicBorrowCell<T> {
+    /// Clones the borrowed value into an independent owned copy
+    ///
+    /// The copy is not tied to the owner, so it can be kept after the borrow and the
+    /// owner are gone.
+    ///
+    /// In debug builds, panics if the owner has been dropped, as `as_ref` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let snapshot = cell.borrow().to_owned();
+    /// drop(cell);
+    ///
+    /// assert_eq!(snapshot, [1, 2, 3]);
+    /// ```
+    pub fn to_owned(&self) -> T {
+        self.as_ref().clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S: BuildHasher> AtomicBorrowCell<HashMap<K, V, S>> {
+    /// Projects the borrow of a map onto the value stored under `key`
+    ///
+    /// The new borrow takes over the existing one. If the key is missing, the borrow is
+    /// released and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    /// use std::collections::HashMap;
+    ///
+    /// let cell = AtomicLendCell::new(HashMap::from([("alpha", 1), ("beta", 2)]));
+    ///
+    /// assert_eq!(cell.borrow().get("beta").as_deref(), Some(&2));
+    /// assert!(cell.borrow().get("gamma").is_none());
+    /// ```
+    pub fn get<Q: ?Sized + Hash + Eq>(self, key: &Q) -> Option<AtomicBorrowCell<V>>
+    where
+        K: core::borrow::Borrow<Q> + Hash + Eq
+    {
+        self.filter_map(|map| map.get(key)).ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> AtomicLendCell<Vec<T>> {
+    /// Creates a new `AtomicBorrowCell` of a range of the contained vector
+    ///
+    /// This is convenient for handing each worker a contiguous chunk of a shared buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the vector, or if it starts after it ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3, 4]);
+    /// let middle = cell.borrow_range(1..3);
+    ///
+    /// assert_eq!(*middle, [2, 3]);
+    /// ```
+    pub fn borrow_range(&self, range: Range<usize>) -> AtomicBorrowCell<[T]> {
+        let len = self.as_ref().len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "Range {range:?} is out of bounds for an AtomicLendCell<Vec<_>> of length {len}"
+        );
+        self.borrow().map(|data| &data[range])
+    }
+
+    /// Creates one `AtomicBorrowCell` per element of the contained vector
+    ///
+    /// The borrows are created lazily as the iterator is advanced, each checking the
+    /// owner's liveness like any other borrow, so elements can be handed to workers one
+    /// at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+    /// let elements: Vec<_> = cell.borrow_each().collect();
+    ///
+    /// assert_eq!(*elements[2], 3);
+    /// ```
+    pub fn borrow_each(&self) -> impl Iterator<Item = AtomicBorrowCell<T>> + '_ {
+        (0..self.as_ref().len()).map(move |i| self.borrow().map(|data| &data[i]))
+    }
+}
+
+/// A cell that lends a value stored on the heap
+///
+/// Created by [`AtomicLendCell::from_box`]. Only the box pointer is moved into the
+/// cell, so large values are lent without being copied, and the value stays at the same
+/// address for as long as the cell exists. Unlike `AtomicLendCell<Box<T>>`, this
+/// dereferences to `T` and hands out borrows of `T`.
+#[cfg(feature = "std")]
+pub struct BoxedLendCell<T: ?Sized>(AtomicLendCell<Box<T>>);
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> AtomicLendCell<T> {
+    /// Creates a cell that lends the boxed value without moving it off the heap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let payload = vec![0u8; 1 << 20].into_boxed_slice();
+    /// let address = payload.as_ptr();
+    ///
+    /// let cell = AtomicLendCell::from_box(payload);
+    /// assert_eq!(cell.as_ptr(), address);
+    /// assert_eq!(cell.borrow()[0], 0);
+    /// ```
+    pub fn from_box(data: Box<T>) -> BoxedLendCell<T> {
+        BoxedLendCell(AtomicLendCell::new(data))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> BoxedLendCell<T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the boxed value
+    pub fn borrow(&self) -> AtomicBorrowCell<T> {
+        self.0.borrow().map(|data| &**data)
+    }
+
+    /// Consumes the cell, returning the box
+    pub fn into_box(self) -> Box<T> {
+        self.0.into_inner()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for BoxedLendCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for BoxedLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxedLendCell").field(&&**self).finish()
+    }
+}
+
+/// Borrowing from a cell that is shared through an `Arc`
+///
+/// The borrows hold a clone of the `Arc` next to the usual borrow, so the cell is kept
+/// alive for as long as they exist and can't be dropped out from under them.
+#[cfg(feature = "std")]
+pub trait ArcLendExt<T: ?Sized> {
+    /// Creates a new `ArcBorrow` that keeps the cell alive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::{AtomicLendCell, ArcLendExt};
+    /// use std::sync::Arc;
+    ///
+    /// let cell = Arc::new(AtomicLendCell::new(42));
+    /// let borrow = cell.borrow();
+    /// drop(cell);
+    ///
+    /// assert_eq!(*borrow, 42);
+    /// ```
+    fn borrow(&self) -> ArcBorrow<T>;
+}
+
+/// A borrow of a cell shared through an `Arc`, created by [`ArcLendExt::borrow`]
+///
+/// It dereferences to the borrowed value directly rather than through the `Arc`.
+#[cfg(feature = "std")]
+pub struct ArcBorrow<T: ?Sized> {
+    // Declared first so the borrow is released before the cell may be dropped
+    borrow: AtomicBorrowCell<T>,
+    owner: Arc<AtomicLendCell<T>>
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> ArcLendExt<T> for Arc<AtomicLendCell<T>> {
+    fn borrow(&self) -> ArcBorrow<T> {
+        ArcBorrow {borrow: AtomicLendCell::borrow(self), owner: Arc::clone(self)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> ArcBorrow<T> {
+    /// Returns the cell this borrow keeps alive
+    pub fn owner(this: &Self) -> &Arc<AtomicLendCell<T>> {
+        &this.owner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for ArcBorrow<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Clone for ArcBorrow<T> {
+    fn clone(&self) -> Self {
+        ArcBorrow {borrow: self.borrow.clone(), owner: Arc::clone(&self.owner)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ArcBorrow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArcBorrow").field(&&**self).finish()
+    }
+}
+
+/// A cell whose liveness flag lives on the heap, shared with its borrows
+///
+/// Created by [`AtomicLendCell::new_shared_flag`]. Each borrow holds a clone of the
+/// `Arc` around the flag, so checking liveness stays sound after the owner has been
+/// dropped, which the epoch of an `AtomicLendCell` can't guarantee once its memory has
+/// been reused. The value is boxed, so moving the owner leaves its borrows pointing at
+/// the value. They still don't keep it alive, so reading through them after the owner
+/// is gone is only safe through `checked_as_ref`.
+#[cfg(feature = "std")]
+pub struct SharedFlagLendCell<T: ?Sized> {
+    alive: Arc<AtomicBool>,
+    data: Box<T>
+}
+
+/// A borrow of a `SharedFlagLendCell` that can check its owner's liveness at any time
+///
+/// Unlike `AtomicBorrowCell`, it may be dropped after its owner.
+#[cfg(feature = "std")]
+pub struct SharedFlagBorrowCell<T: ?Sized> {
+    data_ptr: NonNull<T>,
+    alive: Arc<AtomicBool>
+}
+
+#[cfg(feature = "std")]
+impl<T> AtomicLendCell<T> {
+    /// Creates a cell whose borrows share its liveness flag through an `Arc`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::flag_based::AtomicLendCell;
+    ///
+    /// let cell = AtomicLendCell::new_shared_flag(42);
+    /// let borrow = cell.borrow();
+    /// assert_eq!(borrow.checked_as_ref(), Some(&42));
+    ///
+    /// drop(cell);
+    /// assert!(!borrow.is_alive());
+    /// ```
+    pub fn new_shared_flag(data: T) -> SharedFlagLendCell<T> {
+        SharedFlagLendCell {alive: Arc::new(AtomicBool::new(true)), data: Box::new(data)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> SharedFlagLendCell<T> {
+    /// Returns a reference to the contained value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        &self.data
+    }
+
+    /// Creates a new `SharedFlagBorrowCell` for the contained value
+    pub fn borrow(&self) -> SharedFlagBorrowCell<T> {
+        SharedFlagBorrowCell {data_ptr: NonNull::from(&*self.data), alive: Arc::clone(&self.alive)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for SharedFlagLendCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Drop for SharedFlagLendCell<T> {
+    /// Marks the shared flag as no longer alive
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> SharedFlagBorrowCell<T> {
+    /// Returns `true` if the owner is still alive
+    ///
+    /// This only reads the shared flag, so it is safe to call at any time.
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Acquire)
+    }
+
+    /// Returns a reference to the borrowed value
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the owner has been dropped. In release builds no check
+    /// is performed, and calling this after the owner has been dropped reads freed
+    /// memory. Use `checked_as_ref` where that cannot be ruled out.
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        #[cfg(debug_assertions)]
+        if !self.is_alive() {
+            panic!("Attempting to access SharedFlagBorrowCell<{}> after owner was dropped", core::any::type_name::<T>());
+        }
+        unsafe { self.data_ptr.as_ref() }
+    }
+
+    /// Returns a reference to the borrowed value if the owner is still alive
+    ///
+    /// As with `AtomicBorrowCell::checked_as_ref`, the owner may still be dropped right
+    /// after the check, which only the caller can rule out.
+    pub fn checked_as_ref(&self) -> Option<&T> {
+        if self.is_alive() {
+            Some(unsafe { self.data_ptr.as_ref() })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Deref for SharedFlagBorrowCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Clone for SharedFlagBorrowCell<T> {
+    fn clone(&self) -> Self {
+        SharedFlagBorrowCell {data_ptr: self.data_ptr, alive: Arc::clone(&self.alive)}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SharedFlagLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedFlagLendCell").field("data", &self.as_ref()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SharedFlagBorrowCell<T> {
+    /// Formats the borrowed value if the owner is still alive
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("SharedFlagBorrowCell");
+        match self.checked_as_ref() {
+            Some(data) => d.field("data", &data).finish(),
+            None => d.finish_non_exhaustive()
+        }
+    }
+}
+
+// Like `AtomicBorrowCell`, the borrow only hands out shared references to the value
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+unsafe impl<T: ?Sized + Sync> Send for SharedFlagBorrowCell<T> {}
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+unsafe impl<T: ?Sized + Sync> Sync for SharedFlagBorrowCell<T> {}
+
+/// Creates a cell containing the given value
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// let cell: AtomicLendCell<_> = vec![1, 2, 3].into();
+/// assert_eq!(*cell.borrow(), [1, 2, 3]);
+/// ```
+impl<T> From<T> for AtomicLendCell<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+/// Creates a cell containing the default value of `T`
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// #[derive(Default)]
+/// struct Config {
+///     retries: AtomicLendCell<u32>
+/// }
+///
+/// assert_eq!(*Config::default().retries, 0);
+/// ```
+impl<T: Default> Default for AtomicLendCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for AtomicLendCell<T> {
+    /// Creates an independent cell holding a clone of the value
+    fn clone(&self) -> Self {
+        Self::new(self.as_ref().clone())
+    }
+}
+
+impl<T: ?Sized> Clone for AtomicBorrowCell<T> {
+    /// Creates a new `AtomicBorrowCell` that borrows the same value
+    ///
+    /// Unlike reference counting, this doesn't need to increment any counters,
     /// making it more efficient.
     fn clone(&self) -> Self {
         // Simply create a new borrow pointing to the same data and liveness flag
         AtomicBorrowCell {
             data_ptr: self.data_ptr,
-            owner_alive_ptr: self.owner_alive_ptr
+            owner: self.owner
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AtomicLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicLendCell")
+            .field("data", &self.as_ref())
+            .field("is_alive", &(self.epoch.load(Ordering::Acquire) != DEAD))
+            .finish()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AtomicBorrowCell<T> {
+    /// Formats the borrowed value if the owner is known to be alive
+    ///
+    /// In release builds the liveness of the owner is not checked, so the value is
+    /// never read and only `AtomicBorrowCell { .. }` is printed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("AtomicBorrowCell");
+        #[cfg(debug_assertions)]
+        {
+            let is_alive = self.owner.is_owner_alive();
+            if is_alive {
+                return d.field("data", &unsafe { self.data_ptr.as_ref() }).finish();
+            }
+        }
+        d.finish_non_exhaustive()
+    }
+}
+
+/// A borrow restricted to reading the borrowed value
+///
+/// Created by [`AtomicBorrowCell::read_view`]. It keeps the liveness checks of the
+/// borrow it wraps, but can't be cloned, projected or turned into raw pointers, which
+/// makes it suitable for handing to code that should only look at the value:
+///
+/// ```compile_fail,E0308
+/// use atomic_lend_cell::flag_based::{AtomicLendCell, ReadView};
+///
+/// let cell = AtomicLendCell::new(42);
+/// let view = cell.borrow().read_view();
+/// let copy: ReadView<i32> = view.clone();
+/// ```
+pub struct ReadView<T: ?Sized>(AtomicBorrowCell<T>);
+
+impl<T: ?Sized> ReadView<T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+impl<T: ?Sized> Deref for ReadView<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ReadView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadView").field(&self.0).finish()
+    }
+}
+
+/// Formats the address of the borrowed value
+///
+/// The value itself is never read, so this is fine even after the owner has been
+/// dropped, e.g. to correlate borrows in logs.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// println!("{:p}", borrow);
+///
+/// assert_eq!(format!("{:p}", borrow), format!("{:p}", borrow.as_ptr()));
+/// ```
+impl<T: ?Sized> fmt::Pointer for AtomicBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.data_ptr, f)
+    }
+}
+
+/// Formats the contained value
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// println!("{}", cell);
+/// assert_eq!(format!("{} {}", cell, cell.borrow()), "42 42");
+/// ```
+impl<T: ?Sized + fmt::Display> fmt::Display for AtomicLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_ref(), f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for AtomicBorrowCell<T> {
+    /// Formats the borrowed value
+    ///
+    /// Like `as_ref`, this panics in debug builds if the owner has been dropped.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_ref(), f)
+    }
+}
+
+/// A borrow that remembers the generation of the value it was created for
+///
+/// Created by [`AtomicLendCell::borrow_stamped`]. The cell's generation is bumped
+/// whenever its value may have changed, so a cached borrow can tell that the value has
+/// been reloaded since it was taken, and refresh itself.
+///
+/// Reloading the value still has to uphold the safety contract of
+/// [`AtomicLendCell::replace`]: a stamped borrow may be kept across the reload, but
+/// must not be read while it happens, and references obtained from it before must not
+/// be used afterwards. Checking `is_current` before each use and refreshing a stale
+/// borrow keeps to this:
+///
+/// ```
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// let mut config = AtomicLendCell::new(String::from("v1"));
+/// let mut cached = config.borrow_stamped();
+/// assert_eq!(*cached, "v1");
+///
+/// // The cached borrow is not read during the reload, and nothing derived from it is
+/// // kept across it
+/// unsafe { config.replace(String::from("v2")) };
+/// if !cached.is_current() {
+///     cached = config.borrow_stamped();
+/// }
+/// assert_eq!(*cached, "v2");
+/// ```
+#[cfg(target_has_atomic = "64")]
+pub struct StampedBorrow<T: ?Sized> {
+    borrow: AtomicBorrowCell<T>,
+    generation_ptr: NonNull<AtomicU64>,
+    generation: u64
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T: ?Sized> StampedBorrow<T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        self.borrow.as_ref()
+    }
+
+    /// Returns the generation the borrow was created at
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns `true` if the value hasn't been replaced since the borrow was created
+    ///
+    /// Also returns `false` if the owner has been dropped or has revoked its borrows,
+    /// since the borrow has to be refreshed in either case. As with `checked_as_ref`,
+    /// the owner may still be dropped or reload its value right after the check.
+    pub fn is_current(&self) -> bool {
+        // The generation lives in the owner, so it is only read while the owner is alive
+        self.borrow.owner.is_alive() && unsafe { self.generation_ptr.as_ref() }.load(Ordering::Acquire) == self.generation
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T: ?Sized> Deref for StampedBorrow<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T: ?Sized> Clone for StampedBorrow<T> {
+    fn clone(&self) -> Self {
+        StampedBorrow {borrow: self.borrow.clone(), generation_ptr: self.generation_ptr, generation: self.generation}
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for StampedBorrow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StampedBorrow")
+            .field("borrow", &self.borrow)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+// Stamped borrows only read the generation besides the value, so they share the
+// bounds of `AtomicBorrowCell`
+#[cfg(all(target_has_atomic = "64", not(feature = "no-send")))]
+unsafe impl<T: ?Sized + Sync> Send for StampedBorrow<T> {}
+#[cfg(all(target_has_atomic = "64", not(feature = "no-send")))]
+unsafe impl<T: ?Sized + Sync> Sync for StampedBorrow<T> {}
+
+/// A weak reference to data contained in an `AtomicLendCell`
+///
+/// `AtomicWeakBorrow<T>` is created by `AtomicBorrowCell::downgrade` and, unlike
+/// `AtomicBorrowCell`, may be dropped after the owner. It cannot access the value
+/// directly and must be upgraded first, which fails once the owner has been dropped.
+///
+/// The owner's epoch itself lives inside the owner, so the weak borrow can only detect
+/// the owner's drop while the owner's memory is unused or holds another cell, e.g. for
+/// owners in statics or in pooled allocations that outlive their contents.
+pub struct AtomicWeakBorrow<T: ?Sized> {
+    data_ptr: NonNull<T>,
+    owner: EpochRef
+}
+
+impl<T: ?Sized> AtomicWeakBorrow<T> {
+    /// Creates a new `AtomicBorrowCell` if the owner is still alive
+    ///
+    /// This performs an `Acquire` load of the owner's liveness flag and returns `None`
+    /// if the owner has already been dropped.
+    pub fn upgrade(&self) -> Option<AtomicBorrowCell<T>> {
+        let is_alive = self.owner.is_alive();
+        if is_alive {
+            Some(AtomicBorrowCell {
+                data_ptr: self.data_ptr,
+                owner: self.owner
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for AtomicWeakBorrow<T> {
+    fn clone(&self) -> Self {
+        AtomicWeakBorrow {
+            data_ptr: self.data_ptr,
+            owner: self.owner
         }
     }
 }
 
+impl<T: ?Sized> fmt::Debug for AtomicWeakBorrow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicWeakBorrow").finish_non_exhaustive()
+    }
+}
+
+// Weak borrows only hand out `AtomicBorrowCell`s, so they share their bounds
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Send for AtomicWeakBorrow<T> {}
+#[cfg(not(feature = "no-send"))]
+unsafe impl<T: ?Sized + Sync> Sync for AtomicWeakBorrow<T> {}
+
+impl<T: ?Sized + PartialEq> PartialEq for AtomicLendCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for AtomicLendCell<T> {}
+
+// Comparing or ordering borrows reads the borrowed values, so like `as_ref` this is
+// only checked against a dropped owner in debug builds, and comparing a borrow whose
+// owner has been dropped is undefined behavior in release builds. Use the ref-counting
+// implementation if borrows may be compared after their owner has gone away, e.g. when
+// they are kept in a sorted collection.
+impl<T: ?Sized + PartialEq> PartialEq for AtomicBorrowCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for AtomicBorrowCell<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for AtomicBorrowCell<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.as_ref().partial_cmp(other.as_ref())
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for AtomicBorrowCell<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<AtomicBorrowCell<T>> for AtomicLendCell<T> {
+    fn eq(&self, other: &AtomicBorrowCell<T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<AtomicLendCell<T>> for AtomicBorrowCell<T> {
+    fn eq(&self, other: &AtomicLendCell<T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+/// Allows the cell to be passed to code that is generic over `AsRef<T>`
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::flag_based::AtomicLendCell;
+///
+/// fn takes(x: impl AsRef<i32>) -> i32 {
+///     *x.as_ref()
+/// }
+///
+/// let cell = AtomicLendCell::new(42);
+/// assert_eq!(takes(cell.borrow()), 42);
+/// assert_eq!(takes(&cell), 42);
+/// ```
+impl<T: ?Sized> AsRef<T> for AtomicLendCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for AtomicBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> LendCell<T> for AtomicLendCell<T> {
+    type Borrow = AtomicBorrowCell<T>;
+
+    fn borrow(&self) -> AtomicBorrowCell<T> {
+        AtomicLendCell::borrow(self)
+    }
+
+    fn as_ref(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for AtomicBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for ReadView<T> {
+    fn as_ref(&self) -> &T {
+        ReadView::as_ref(self)
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T: ?Sized> BorrowHandle<T> for StampedBorrow<T> {
+    fn as_ref(&self) -> &T {
+        StampedBorrow::as_ref(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> BorrowHandle<T> for ArcBorrow<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> BorrowHandle<T> for SharedFlagBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        SharedFlagBorrowCell::as_ref(self)
+    }
+}
+
+// `Borrow` is not imported, since its `borrow` would take precedence over the inherent
+// `borrow` of cells behind smart pointers such as `Box`
+impl<T: ?Sized> core::borrow::Borrow<T> for AtomicLendCell<T> {
+    fn borrow(&self) -> &T {
+        AtomicLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> core::borrow::Borrow<T> for AtomicBorrowCell<T> {
+    fn borrow(&self) -> &T {
+        AtomicBorrowCell::as_ref(self)
+    }
+}
+
+// Hashes the contained value, consistent with the `PartialEq` implementations
+impl<T: ?Sized + Hash> Hash for AtomicLendCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for AtomicBorrowCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
 #[test]
+#[cfg(not(feature = "no-send"))]
 /// Tests that borrowing works across threads
 fn test_epoch_borrow() {
     let x = AtomicLendCell::new(4);
@@ -205,38 +2018,767 @@ fn test_epoch_borrow() {
 
 #[test]
 /// Tests the safety checks for owner outliving borrows
+///
+/// Only sound paths are exercised, so this also passes under `cargo +nightly miri test`:
+/// the owner is dropped in place, so the liveness flag stays readable afterwards.
 fn test_epoch_safety() {
-    use std::sync::Arc;
-    
-    // This test will only panic in debug builds
+    use std::{mem::ManuallyDrop, sync::Arc};
+
     let data = Arc::new(42);
     let data_clone = Arc::clone(&data);
-    
-    let x_opt = Some(AtomicLendCell::new(data));
-    let borrow = x_opt.as_ref().unwrap().borrow();
-    
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(data));
+    let borrow = x.borrow();
+
     // Use the borrow before dropping owner
     assert_eq!(**borrow, 42);
-    
+
     // Simulate work in another thread
     let handle = std::thread::spawn(move || {
         // Just hold onto data_clone to ensure it doesn't drop
         assert_eq!(*data_clone, 42);
-        std::thread::sleep(std::time::Duration::from_millis(50));
     });
-    
+
     // Drop the owner while borrow still exists
-    drop(x_opt);
-    
-    // In debug builds, this would panic when checking borrow's liveness
+    unsafe { ManuallyDrop::drop(&mut x) };
+
+    // The checked access notices the dropped owner in every build
+    assert!(borrow.checked_as_ref().is_none());
+    // Dropping the borrow would panic in debug builds
+    mem::forget(borrow);
+
+    handle.join().unwrap();
+}
+
+#[test]
+/// Tests that `try_borrow` refuses to lend once the owner has been dropped
+fn test_try_borrow_after_drop() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    assert_eq!(*x.try_borrow().unwrap(), 4);
+
+    // Run the owner's destructor while keeping its memory around
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert_eq!(x.try_borrow().unwrap_err(), BorrowError::OwnerDropped);
+}
+
+#[test]
+/// Tests that `into_inner` moves the value out of the cell
+fn test_into_inner() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    assert_eq!(x.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+/// Tests the `Debug` output of the cell and its borrows
+fn test_debug() {
+    let x = AtomicLendCell::new(4);
+    let b = x.borrow();
+    assert_eq!(format!("{:?}", x), "AtomicLendCell { data: 4, is_alive: true }");
+    #[cfg(debug_assertions)]
+    assert_eq!(format!("{:?}", b), "AtomicBorrowCell { data: 4 }");
     #[cfg(not(debug_assertions))]
-    {
-        // This should only run in release builds
-        std::thread::sleep(std::time::Duration::from_millis(10));
-        
-        // This will cause undefined behavior in release mode if safety is violated
-        let _value = *borrow;
+    assert_eq!(format!("{:?}", b), "AtomicBorrowCell { .. }");
+}
+
+#[test]
+/// Tests that `checked_as_ref` detects the owner's drop in every build profile
+fn test_checked_as_ref() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    assert_eq!(xr.checked_as_ref(), Some(&4));
+
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert_eq!(xr.checked_as_ref(), None);
+
+    // Dropping the borrow now would panic in debug builds
+    std::mem::forget(xr);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests lending a slice borrow out of a cell holding an array
+fn test_unsized_borrow() {
+    let x: Box<AtomicLendCell<[i32]>> = Box::new(AtomicLendCell::new([1, 2, 3, 4]));
+    let xr: AtomicBorrowCell<[i32]> = x.borrow();
+    let t = std::thread::spawn(move || xr.iter().sum::<i32>());
+    assert_eq!(t.join().unwrap(), 10);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests projecting a borrow onto a field
+fn test_map() {
+    let x = AtomicLendCell::new((4u32, String::from("four")));
+    let xr = x.borrow().map(|pair| &pair.1);
+    let t = std::thread::spawn(move || {
+        assert_eq!(*xr, "four");
+    });
+    t.join().unwrap();
+}
+
+#[test]
+/// Tests that weak borrows can only be upgraded while the owner is alive
+fn test_weak_upgrade() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let weak = x.borrow().downgrade();
+    assert_eq!(*weak.upgrade().unwrap(), 4);
+
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+/// Tests that cells and borrows compare by their contained values
+fn test_eq() {
+    let x = AtomicLendCell::new(4);
+    let y = AtomicLendCell::new(4);
+    let z = AtomicLendCell::new(5);
+    assert!(x == y);
+    assert!(x != z);
+    assert!(x.borrow() == y.borrow());
+    assert!(x.borrow() != z.borrow());
+    assert!(x == y.borrow());
+    assert!(z.borrow() != x);
+}
+
+#[test]
+/// Tests lending a scoped borrow to scoped threads
+fn test_with_scope() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let sum = x.with_scope(|xr| {
+        std::thread::scope(|s| {
+            let t1 = s.spawn(move || xr.iter().sum::<i32>());
+            let t2 = s.spawn(move || xr.len() as i32);
+            t1.join().unwrap() + t2.join().unwrap()
+        })
+    });
+    assert_eq!(sum, 9);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that a replaced value is seen by existing borrows
+fn test_replace() {
+    let mut x = AtomicLendCell::new(String::from("old"));
+    let xr = x.borrow();
+    // The borrow is only read once the value has been replaced
+    assert_eq!(unsafe { x.replace(String::from("new")) }, "old");
+    let t = std::thread::spawn(move || assert_eq!(*xr, "new"));
+    t.join().unwrap();
+}
+
+#[test]
+/// Tests that a cell can be shared whenever its value can, even if the value can't be sent
+fn test_sync_without_send() {
+    fn assert_sync<S: Sync>() {}
+    assert_sync::<AtomicLendCell<std::sync::MutexGuard<'static, i32>>>();
+}
+
+#[test]
+/// Tests that borrows of equal values are deduplicated in a `HashSet`
+fn test_hash() {
+    use std::collections::HashSet;
+
+    let x = AtomicLendCell::new(String::from("shared"));
+    let y = AtomicLendCell::new(String::from("shared"));
+    let z = AtomicLendCell::new(String::from("other"));
+    let set: HashSet<_> = [x.borrow(), y.borrow(), x.borrow(), z.borrow()].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&String::from("shared")));
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that a dangling borrow dropped during unwinding does not cause a double panic
+fn test_drop_while_panicking() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    unsafe { ManuallyDrop::drop(&mut x) };
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+        let _xr = xr;
+        panic!("original panic");
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"original panic"));
+}
+
+#[test]
+/// Tests that a fresh borrow points at the cell's value
+fn test_as_ptr() {
+    let x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    assert_eq!(x.as_ptr(), xr.as_ptr());
+    assert_eq!(unsafe { *xr.as_ptr() }, 4);
+}
+
+#[test]
+/// Tests that a borrow survives a round trip through raw pointers
+fn test_raw_round_trip() {
+    let x = AtomicLendCell::new(String::from("raw"));
+    let (data, alive) = x.borrow().into_raw();
+    let xr = unsafe { AtomicBorrowCell::from_raw(data, alive) };
+    assert_eq!(*xr, "raw");
+    assert_eq!(xr.as_ptr(), x.as_ptr());
+}
+
+#[test]
+/// Tests using the cell through the backend traits
+fn test_traits() {
+    fn sum<C: LendCell<[i32; 2]> + ?Sized>(cell: &C) -> i32 {
+        let borrow = cell.borrow();
+        borrow.as_ref()[0] + cell.as_ref()[1]
     }
-    
-    handle.join().unwrap();
-}
\ No newline at end of file
+    let x = AtomicLendCell::new([1, 2]);
+    assert_eq!(sum(&x), 3);
+
+    let dynamic: &dyn LendCell<[i32; 2], Borrow = AtomicBorrowCell<[i32; 2]>> = &x;
+    assert_eq!(sum(dynamic), 3);
+}
+
+#[test]
+/// Tests swapping the values of two quiescent cells
+fn test_swap() {
+    let mut x = AtomicLendCell::new(String::from("front"));
+    let mut y = AtomicLendCell::new(String::from("back"));
+    unsafe { x.swap(&mut y) };
+    assert_eq!((x.as_ref().as_str(), y.as_ref().as_str()), ("back", "front"));
+}
+
+#[test]
+/// Tests that `take` returns the value and leaves the default behind
+fn test_take() {
+    let mut x = AtomicLendCell::new(vec![1, 2, 3]);
+    assert_eq!(unsafe { x.take() }, vec![1, 2, 3]);
+    assert!(x.borrow().is_empty());
+}
+
+#[test]
+/// Tests that a cloned cell holds its own copy of the value
+fn test_clone_cell() {
+    let mut x = AtomicLendCell::new(vec![1, 2, 3]);
+    let y = x.clone();
+    unsafe { x.replace(vec![4]) };
+    assert_eq!(*y.borrow(), [1, 2, 3]);
+}
+
+#[test]
+/// Tests both outcomes of projecting a borrow with `filter_map`
+fn test_filter_map() {
+    enum Shape {
+        Circle(f64),
+        Square(f64)
+    }
+    let x = AtomicLendCell::new([Shape::Circle(1.5), Shape::Square(2.0)]);
+    let square = x.borrow().map(|shapes| &shapes[0]).filter_map(|shape| match shape {
+        Shape::Square(side) => Some(side),
+        _ => None
+    });
+    let xr = square.err().unwrap();
+    let radius = xr.filter_map(|shape| match shape {
+        Shape::Circle(radius) => Some(radius),
+        _ => None
+    });
+    assert_eq!(*radius.ok().unwrap(), 1.5);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests handing the halves of a split borrow to separate threads
+fn test_split() {
+    let x = AtomicLendCell::new((4, String::from("four")));
+    let (number, text) = x.borrow().split(|pair| (&pair.0, pair.1.as_str()));
+    let t1 = std::thread::spawn(move || assert_eq!(*number, 4));
+    let t2 = std::thread::spawn(move || assert_eq!(&*text, "four"));
+    t1.join().unwrap();
+    t2.join().unwrap();
+}
+
+#[test]
+/// Tests that `Display` forwards to the value
+fn test_display() {
+    let x = AtomicLendCell::new(String::from("shown"));
+    assert_eq!(format!("{:>6}|{}", x, x.borrow()), " shown|shown");
+}
+
+#[test]
+/// Tests that a stale borrow is not revived by a new cell at the same address
+fn test_epoch_address_reuse() {
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(1));
+    let stale = x.borrow();
+    unsafe { ManuallyDrop::drop(&mut x) };
+
+    // Construct a new cell in the same memory
+    x = ManuallyDrop::new(AtomicLendCell::new(2));
+    assert!(stale.checked_as_ref().is_none());
+    assert!(stale.downgrade().upgrade().is_none());
+    assert_eq!(*x.borrow(), 2);
+
+    // Dropping the stale borrow would panic in debug builds
+    mem::forget(stale);
+    unsafe { ManuallyDrop::drop(&mut x) };
+}
+
+#[test]
+/// Tests that `try_clone` only clones borrows of a live owner
+fn test_try_clone() {
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    let xr = x.borrow();
+    assert_eq!(*xr.try_clone().unwrap(), 4);
+
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert!(xr.try_clone().is_none());
+    // Dropping the borrow would panic in debug builds
+    mem::forget(xr);
+}
+
+#[test]
+/// Tests the state reported before and after the owner is dropped
+fn test_state() {
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(4));
+    assert_eq!(x.state(), CellState::Alive);
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert_eq!(format!("{:?}", x.state()), "Dropped");
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests lending the target of a reference to another thread
+fn test_try_borrow_deref() {
+    let value = vec![1, 2, 3];
+    let x = AtomicLendCell::new(&value);
+    let xr = x.try_borrow_deref().unwrap();
+    std::thread::scope(|s| {
+        s.spawn(move || assert_eq!(*xr, [1, 2, 3]));
+    });
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests borrowing from a cell in a static
+fn test_static() {
+    static CONFIG: AtomicLendCell<u32> = AtomicLendCell::new(7);
+    let xr = CONFIG.borrow();
+    let t = std::thread::spawn(move || assert_eq!(*xr, 7));
+    t.join().unwrap();
+}
+
+#[test]
+/// Tests that `ptr_eq` compares the borrowed addresses rather than the values
+fn test_ptr_eq() {
+    let x = AtomicLendCell::new(4);
+    let y = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    assert!(AtomicBorrowCell::ptr_eq(&xr, &xr.clone()));
+    assert!(!AtomicBorrowCell::ptr_eq(&xr, &y.borrow()));
+    assert_eq!(xr, y.borrow());
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests spawning workers that each get a borrow
+fn test_scope() {
+    let x = AtomicLendCell::new(String::from("shared"));
+    let lengths: Vec<usize> = x.scope(|s| {
+        let workers: Vec<_> = (0..3).map(|_| s.spawn_with_borrow(|xr| xr.len())).collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+    assert_eq!(lengths, [6, 6, 6]);
+}
+
+#[test]
+/// Tests several reads of an atomic value inside one `with` call
+fn test_with() {
+    let x = AtomicLendCell::new(AtomicUsize::new(1));
+    let xr = x.borrow();
+    let sum = xr.with(|counter| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        counter.fetch_add(1, Ordering::Relaxed);
+        counter.load(Ordering::Relaxed) + counter.load(Ordering::Relaxed)
+    });
+    assert_eq!(sum, 6);
+    assert_eq!(x.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that a boxed value stays at the same address when lent
+fn test_from_box() {
+    let payload = vec![7u8; 1 << 20].into_boxed_slice();
+    let address = payload.as_ptr();
+    let x = AtomicLendCell::from_box(payload);
+    assert_eq!(x.as_ptr(), address);
+    let xr = x.borrow();
+    assert_eq!(xr.as_ptr().cast::<u8>(), address);
+    assert_eq!(xr[1 << 19], 7);
+    drop(xr);
+    let payload = x.into_box();
+    assert_eq!(payload.as_ptr(), address);
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests handing out non-overlapping chunks of a vector to several threads
+fn test_borrow_range() {
+    let x = AtomicLendCell::new((0..12).collect::<Vec<u8>>());
+    let workers: Vec<_> = (0..3)
+        .map(|i| {
+            let chunk = x.borrow_range(i * 4..i * 4 + 4);
+            std::thread::spawn(move || chunk.iter().map(|&b| b as u32).sum::<u32>())
+        })
+        .collect();
+    let sums: Vec<u32> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+    assert_eq!(sums, [6, 22, 38]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "out of bounds")]
+/// Tests that borrowing a range past the end of the vector panics
+fn test_borrow_range_out_of_bounds() {
+    let x = AtomicLendCell::new(vec![1u8, 2, 3]);
+    let _xr = x.borrow_range(2..4);
+}
+
+#[test]
+/// Tests that `read_or` falls back to the default once the owner is dropped
+fn test_read_or() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(8080));
+    let xr = x.borrow();
+    assert_eq!(xr.read_or(|| 80, |port| *port), 8080);
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert_eq!(xr.read_or(|| 80, |port| *port), 80);
+    mem::forget(xr);
+}
+
+#[test]
+/// Tests sorting borrows by their values
+fn test_sort_borrows() {
+    let a = AtomicLendCell::new("pear");
+    let b = AtomicLendCell::new("apple");
+    let c = AtomicLendCell::new("fig");
+    let mut borrows = [a.borrow(), b.borrow(), c.borrow()];
+    borrows.sort();
+    let sorted: Vec<&str> = borrows.iter().map(|xr| **xr).collect();
+    assert_eq!(sorted, ["apple", "fig", "pear"]);
+    assert!(a.borrow() > b.borrow());
+}
+
+#[test]
+/// Tests that borrows of a zero-sized value track their owner like any other
+fn test_zero_sized() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(()));
+    let xr = x.borrow();
+    let xr2 = xr.clone();
+    assert_eq!(*xr2, ());
+    assert_eq!(xr.checked_as_ref(), Some(&()));
+    drop(xr2);
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert_eq!(xr.checked_as_ref(), None);
+    mem::forget(xr);
+
+    let y = AtomicLendCell::new((4, ()));
+    let yr = y.borrow().map(|pair| &pair.1);
+    assert_eq!(*yr, ());
+    assert!(yr.checked_as_ref().is_some());
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests handing each element of a vector to a different thread
+fn test_borrow_each() {
+    let x = AtomicLendCell::new((1..=10).collect::<Vec<i32>>());
+    let workers: Vec<_> = x
+        .borrow_each()
+        .map(|element| std::thread::spawn(move || *element * 2))
+        .collect();
+    let sum: i32 = workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+    assert_eq!(sum, 110);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests lending the value of a nested cell to another thread
+fn test_borrow_inner() {
+    let x = AtomicLendCell::new(AtomicLendCell::new(String::from("inner")));
+    let xr = x.borrow_inner();
+    let t = std::thread::spawn(move || xr.len());
+    assert_eq!(t.join().unwrap(), 5);
+    let layered = AtomicLendCell::new(AtomicLendCell::new(AtomicLendCell::new(7)));
+    let inner = layered.borrow().map(|outer| outer.as_ref());
+    assert_eq!(**inner, 7);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+/// Tests that the panic of a borrow read after its owner was dropped names the value's type
+fn test_dead_owner_type_name() {
+    use std::mem::ManuallyDrop;
+
+    let mut x = ManuallyDrop::new(AtomicLendCell::new(String::from("config")));
+    let xr = x.borrow();
+    unsafe { ManuallyDrop::drop(&mut x) };
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| xr.len()));
+    let message = panic.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("AtomicBorrowCell<alloc::string::String>"), "{message}");
+    mem::forget(xr);
+}
+
+#[test]
+/// Tests reading a borrow several times through a guard
+fn test_as_guard() {
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let xr = x.borrow();
+    let guard = xr.as_guard();
+    let total: i32 = guard.iter().sum::<i32>() + guard[2] + guard.len() as i32;
+    assert_eq!(total, 12);
+}
+
+#[test]
+#[cfg(feature = "internals")]
+/// Tests revoking and reinstating borrows through the liveness epoch
+fn test_liveness_handshake() {
+    let x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    let liveness = unsafe { x.liveness() };
+    let epoch = liveness.swap(DEAD, Ordering::AcqRel);
+    assert_ne!(epoch, UNASSIGNED);
+    assert!(xr.checked_as_ref().is_none());
+    assert_eq!(x.state(), CellState::Dropped);
+    liveness.store(epoch, Ordering::Release);
+    assert_eq!(xr.checked_as_ref(), Some(&4));
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+#[cfg(feature = "std")]
+/// Tests that an `ArcBorrow` keeps reading the value after the original `Arc` is dropped
+fn test_arc_borrow_outlives_arc() {
+    let x = Arc::new(AtomicLendCell::new(String::from("shared")));
+    let xr = ArcLendExt::borrow(&x);
+    drop(x);
+    let handle = std::thread::spawn(move || xr.len());
+    assert_eq!(handle.join().unwrap(), 6);
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+#[cfg(feature = "std")]
+/// Tests borrowing a single map entry on another thread
+fn test_get_map_entry() {
+    let x = AtomicLendCell::new(std::collections::HashMap::from([(1, String::from("one")), (2, String::from("two"))]));
+    let entry = x.borrow().get(&2).unwrap();
+    assert!(x.borrow().get(&3).is_none());
+    let handle = std::thread::spawn(move || entry.as_str().to_owned());
+    assert_eq!(handle.join().unwrap(), "two");
+}
+
+#[test]
+/// Tests that a projected borrow shares its owner with its source, but not with another cell
+fn test_same_owner() {
+    let x = AtomicLendCell::new((1, String::from("one")));
+    let y = AtomicLendCell::new((1, String::from("one")));
+    let xr = x.borrow();
+    let field = xr.clone().map(|pair| &pair.1);
+    let yr = y.borrow();
+    assert!(AtomicBorrowCell::same_owner(&xr, &field));
+    assert!(AtomicBorrowCell::same_owner(&field, &xr));
+    assert!(!AtomicBorrowCell::same_owner(&xr, &yr));
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests that a `ReadView` reads the value and is `Send` and `Sync` like the borrow it wraps
+fn test_read_view() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    let x = AtomicLendCell::new(vec![1, 2, 3]);
+    let view = x.borrow().read_view();
+    assert_send_sync(&view);
+    assert_eq!(view.as_ref(), &[1, 2, 3]);
+    let handle = std::thread::spawn(move || view.iter().sum::<i32>());
+    assert_eq!(handle.join().unwrap(), 6);
+}
+
+#[test]
+/// Tests that the value's address is stable for a cell that stays in place, but not across a move
+fn test_address_stable_until_moved() {
+    let x = AtomicLendCell::new([0u8; 64]);
+    let first = x.borrow().as_ptr();
+    let xr = x.borrow();
+    assert_eq!(xr.as_ptr(), first);
+    assert_eq!(x.as_ptr(), first);
+    drop(xr);
+    // With no borrows outstanding the cell may be moved, which changes the address
+    let moved = std::vec![x];
+    assert_ne!(moved[0].as_ptr(), first);
+    // Keeping the cell in a box makes the address survive moving the box
+    let boxed = std::boxed::Box::new(AtomicLendCell::new([0u8; 64]));
+    let xr = boxed.borrow();
+    let moved_box = boxed;
+    assert_eq!(xr.as_ptr(), moved_box.as_ptr());
+    drop(xr);
+}
+
+#[test]
+/// Tests that `try_map` projects while the owner is alive and returns `None` once it is dropped
+fn test_try_map() {
+    let mut x = ManuallyDrop::new(AtomicLendCell::new((1, String::from("one"))));
+    let xr = x.borrow();
+    let field = xr.clone().try_map(|pair| &pair.1).unwrap();
+    assert_eq!(field.as_str(), "one");
+    drop(field);
+    unsafe { ManuallyDrop::drop(&mut x) };
+    assert!(xr.try_map(|pair| &pair.1).is_none());
+}
+
+#[test]
+/// Tests that revoking fails checked reads through old and new borrows until the cell is reinstated
+fn test_revoke_reinstate() {
+    let x = AtomicLendCell::new(4);
+    let xr = x.borrow();
+    let weak = xr.downgrade();
+    x.revoke();
+    assert!(x.is_revoked());
+    let late = x.borrow();
+    assert!(xr.checked_as_ref().is_none());
+    assert!(late.checked_as_ref().is_none());
+    assert!(weak.upgrade().is_none());
+    assert_eq!(x.state(), CellState::Alive);
+    x.reinstate();
+    assert!(!x.is_revoked());
+    assert_eq!(xr.checked_as_ref(), Some(&4));
+    assert_eq!(late.checked_as_ref(), Some(&4));
+    assert!(weak.upgrade().is_some());
+}
+
+#[test]
+/// Tests revoking a cell that was never borrowed
+fn test_revoke_unborrowed() {
+    let x = AtomicLendCell::new(4);
+    assert!(!x.is_revoked());
+    x.revoke();
+    assert!(x.is_revoked());
+    let xr = x.borrow();
+    assert!(xr.checked_as_ref().is_none());
+    // Borrows of a revoked cell may still be dropped
+    drop(xr);
+    x.reinstate();
+    assert_eq!(x.borrow().checked_as_ref(), Some(&4));
+}
+
+#[test]
+/// Tests that reinstating a cell that was never borrowed leaves it without an epoch
+fn test_reinstate_unborrowed() {
+    let x = AtomicLendCell::new(4);
+    let y = AtomicLendCell::new(5);
+    x.reinstate();
+    y.reinstate();
+    assert!(!x.is_revoked());
+    assert_eq!(x.epoch.load(Ordering::Relaxed), UNASSIGNED);
+    let (xr, yr) = (x.borrow(), y.borrow());
+    assert_ne!(xr.owner.epoch, yr.owner.epoch);
+    assert_eq!(xr.checked_as_ref(), Some(&4));
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// Tests that a borrow detached from a moved and dropped owner safely observes the drop
+fn test_shared_flag_detached() {
+    let x = AtomicLendCell::new_shared_flag(String::from("flag"));
+    let xr = x.borrow();
+    assert_eq!(xr.as_str(), "flag");
+    let xr2 = xr.clone();
+    drop(xr);
+    // The flag and the value are on the heap, so moving the owner doesn't leave either
+    // dangling, and dropping it is observed through the flag
+    let moved = std::vec![x];
+    assert_eq!(xr2.checked_as_ref().map(String::as_str), Some("flag"));
+    drop(moved);
+    assert!(!xr2.is_alive());
+    assert!(xr2.checked_as_ref().is_none());
+    drop(xr2);
+}
+
+#[test]
+/// Tests transforming the value into a new cell and borrowing from it
+fn test_map_owner() {
+    let x = AtomicLendCell::new(42u32);
+    drop(x.borrow());
+    let y = x.map_owner(|n| n.to_string());
+    let yr = y.borrow();
+    assert_eq!(yr.checked_as_ref().map(String::as_str), Some("42"));
+}
+
+#[test]
+/// Tests that a value cloned out of a borrow is independent of the cell
+fn test_to_owned() {
+    let mut x = AtomicLendCell::new(String::from("before"));
+    let xr = x.borrow();
+    let mut snapshot = xr.to_owned();
+    drop(xr);
+    snapshot.push_str(" and after");
+    x.get_mut().unwrap().replace_range(.., "changed");
+    assert_eq!(snapshot, "before and after");
+    assert_eq!(x.as_ref(), "changed");
+}
+
+#[test]
+/// Tests that changing the value makes stamped borrows stale until they are refreshed
+fn test_stamped_borrow_is_current() {
+    let mut x = AtomicLendCell::new(String::from("v1"));
+    let mut xr = x.borrow_stamped();
+    let xr2 = xr.clone();
+    assert_eq!(xr.generation(), 0);
+    assert!(xr.is_current() && xr2.is_current());
+    assert_eq!(xr.len(), 2);
+
+    // Neither borrow is read during the replacement, and no reference derived from
+    // them is kept across it
+    unsafe { x.replace(String::from("v2")) };
+    assert!(!xr.is_current() && !xr2.is_current());
+    assert_eq!(x.generation(), 1);
+    drop(xr2);
+    xr = x.borrow_stamped();
+    assert!(xr.is_current());
+    assert_eq!(*xr, "v2");
+
+    x.get_mut().unwrap().push('!');
+    assert!(!xr.is_current());
+    xr = x.borrow_stamped();
+    assert_eq!(*xr, "v2!");
+
+    x.revoke();
+    assert!(!xr.is_current());
+    x.reinstate();
+    assert!(xr.is_current());
+}
+
+#[test]
+/// Tests reads through every way of deriving a borrow's pointer
+///
+/// No pointer is derived from an integer, so this also passes under
+/// `MIRIFLAGS=-Zmiri-strict-provenance cargo +nightly miri test`.
+fn test_pointer_provenance() {
+    let x = AtomicLendCell::new((String::from("first"), [1u8, 2, 3]));
+    let (data, owner) = x.borrow().map(|pair| &pair.1[1..]).into_raw();
+    let tail = unsafe { AtomicBorrowCell::from_raw(data, owner) };
+    assert_eq!(*tail, [2, 3]);
+    assert_eq!(x.borrow().map(|pair| pair.0.as_str()).as_ref(), "first");
+    drop(tail);
+
+    let value = 7;
+    let y = AtomicLendCell::new(&value);
+    assert_eq!(*y.borrow_deref(), 7);
+}
@@ -1,8 +1,25 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// Borrows only ever derive their pointers from references, so no cast may go through
+// an integer and lose the provenance of the cell they point into
+#![cfg_attr(feature = "strict-provenance", feature(strict_provenance_lints), deny(fuzzy_provenance_casts, lossy_provenance_casts))]
+
 pub mod atomic_counting;
+#[cfg(doctest)]
+mod compile_fail;
+pub mod error;
 pub mod flag_based;
+pub mod local;
+#[cfg(feature = "std")]
+pub mod owned;
+mod padded;
+pub mod scoped;
+pub mod traits;
+
+pub use error::BorrowError;
+pub use scoped::{BorrowGuard, ScopedBorrow};
 
 // Export the implementation based on the selected feature
-#[cfg(feature = "ref-counting")]
+#[cfg(all(feature = "ref-counting", not(feature = "flag-based")))]
 pub use atomic_counting::*;
 
 #[cfg(feature = "flag-based")]
@@ -11,3 +28,11 @@ pub use flag_based::*;
 // If neither feature is explicitly selected, use the default (flag-based)
 #[cfg(all(not(feature = "ref-counting"), not(feature = "flag-based")))]
 pub use flag_based::*;
+
+// With both features enabled, the flag-based implementation keeps the plain names and
+// each backend is additionally available under its own aliases
+#[cfg(all(feature = "ref-counting", feature = "flag-based"))]
+pub use atomic_counting::{AtomicBorrowCell as CountingBorrowCell, AtomicLendCell as CountingLendCell};
+
+#[cfg(all(feature = "ref-counting", feature = "flag-based"))]
+pub use flag_based::{AtomicBorrowCell as FlagBorrowCell, AtomicLendCell as FlagLendCell};
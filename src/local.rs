@@ -0,0 +1,315 @@
+//! # Local Lend Cells
+//!
+//! A single-threaded counterpart of the reference-counting implementation.
+//!
+//! `LocalLendCell<T>` counts its borrows in a plain `Cell<usize>` instead of an atomic,
+//! which makes borrowing and dropping borrows considerably cheaper. In exchange, neither
+//! the cell nor its `LocalBorrowCell`s can be sent to or shared with other threads. This
+//! suits code that only lends values across call frames of one thread, such as tasks on
+//! a single-threaded executor, while keeping the check that no borrow outlives its owner.
+
+use crate::traits::{BorrowHandle, LendCell};
+use core::{any, cell::Cell, fmt, marker::PhantomData, mem::ManuallyDrop, ops::Deref, ptr::{self, NonNull}};
+
+/// A container that lends its contained value within a single thread
+///
+/// Like the reference-counting `AtomicLendCell`, it panics when dropped while borrows
+/// are outstanding, and borrows point into the cell itself, so it has to stay in place
+/// while borrowed.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::local::LocalLendCell;
+///
+/// let cell = LocalLendCell::new(vec![1, 2, 3]);
+/// let borrow = cell.borrow();
+///
+/// assert_eq!(borrow.len(), 3);
+/// assert_eq!(cell.borrow_count(), 1);
+/// ```
+///
+/// Its borrows update the count from the thread that created them, so the cell itself
+/// can't be sent to another thread either, not even by mutable reference:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::local::LocalLendCell;
+///
+/// let mut cell = LocalLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::scope(|s| {
+///     s.spawn(|| cell.get_mut().is_some());
+///     drop(borrow);
+/// });
+/// ```
+pub struct LocalLendCell<T: ?Sized> {
+    count: Cell<usize>,
+    // Keeps the cell from being `Send`, which `Cell` alone only does for `Sync`
+    _local: PhantomData<*const ()>,
+    data: T
+}
+
+/// A reference to data contained in a `LocalLendCell`
+///
+/// It decrements the borrow count of its owner when dropped. It holds raw pointers, so
+/// it is neither `Send` nor `Sync`:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::local::LocalLendCell;
+///
+/// let cell = LocalLendCell::new(42);
+/// let borrow = cell.borrow();
+/// std::thread::spawn(move || *borrow);
+/// ```
+pub struct LocalBorrowCell<T: ?Sized> {
+    data_ptr: NonNull<T>,
+    count_ptr: NonNull<Cell<usize>>
+}
+
+impl<T> LocalLendCell<T> {
+    /// Creates a new `LocalLendCell` containing the given value
+    pub const fn new(data: T) -> Self {
+        Self {count: Cell::new(0), _local: PhantomData, data}
+    }
+
+    /// Consumes the cell and returns the contained value
+    ///
+    /// # Panics
+    ///
+    /// Panics if borrows are still outstanding. In that case the contained value is
+    /// leaked rather than dropped.
+    pub fn into_inner(self) -> T {
+        // Suppress the destructor so the data is moved out exactly once
+        let this = ManuallyDrop::new(self);
+        if this.is_borrowed() {
+            borrows_outlive_owner(any::type_name::<T>());
+        }
+        unsafe {ptr::read(&this.data)}
+    }
+}
+
+impl<T: ?Sized> LocalLendCell<T> {
+    /// Returns a reference to the contained value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        &self.data
+    }
+
+    /// Returns a mutable reference to the contained value if it isn't borrowed
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.is_borrowed() {
+            None
+        } else {
+            Some(&mut self.data)
+        }
+    }
+
+    /// Creates a new `LocalBorrowCell` for the contained value
+    pub fn borrow(&self) -> LocalBorrowCell<T> {
+        self.count.set(self.count.get() + 1);
+        LocalBorrowCell {data_ptr: NonNull::from(&self.data), count_ptr: NonNull::from(&self.count)}
+    }
+
+    /// Returns the number of outstanding `LocalBorrowCell`s
+    pub fn borrow_count(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Returns `true` if any `LocalBorrowCell` is outstanding
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow_count() > 0
+    }
+}
+
+impl<T: ?Sized> Deref for LocalLendCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Drop for LocalLendCell<T> {
+    /// Ensures no borrows exist when the cell is dropped
+    ///
+    /// Panics if borrows are still outstanding, or aborts the process if the
+    /// `abort-on-violation` feature is enabled.
+    fn drop(&mut self) {
+        if self.is_borrowed() {
+            borrows_outlive_owner(any::type_name::<T>());
+        }
+    }
+}
+
+/// Reports a `LocalBorrowCell` that outlives its owner, naming the type of the value
+#[cold]
+fn borrows_outlive_owner(type_name: &str) -> ! {
+    #[cfg(feature = "abort-on-violation")]
+    {
+        std::eprintln!("A LocalBorrowCell<{type_name}> outlives the LocalLendCell which issues it!");
+        std::process::abort();
+    }
+    #[cfg(not(feature = "abort-on-violation"))]
+    panic!("A LocalBorrowCell<{type_name}> outlives the LocalLendCell which issues it!");
+}
+
+impl<T: ?Sized> LocalBorrowCell<T> {
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        unsafe {self.data_ptr.as_ref()}
+    }
+
+    /// Projects the borrow onto a part of the borrowed value
+    ///
+    /// The borrow count is unchanged, since the new borrow takes over the existing one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::local::LocalLendCell;
+    ///
+    /// let cell = LocalLendCell::new((1, String::from("hello")));
+    /// let text = cell.borrow().map(|pair| pair.1.as_str());
+    ///
+    /// assert_eq!(&*text, "hello");
+    /// ```
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(self, f: F) -> LocalBorrowCell<U> {
+        // Run the closure first, so that if it panics `self` is dropped and released
+        let data_ptr = NonNull::from(f(self.as_ref()));
+        let this = ManuallyDrop::new(self);
+        LocalBorrowCell {data_ptr, count_ptr: this.count_ptr}
+    }
+}
+
+impl<T: ?Sized> Deref for LocalBorrowCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Clone for LocalBorrowCell<T> {
+    /// Creates a new `LocalBorrowCell` that borrows the same value
+    fn clone(&self) -> Self {
+        let count = unsafe {self.count_ptr.as_ref()};
+        count.set(count.get() + 1);
+        LocalBorrowCell {data_ptr: self.data_ptr, count_ptr: self.count_ptr}
+    }
+}
+
+impl<T: ?Sized> Drop for LocalBorrowCell<T> {
+    /// Decrements the borrow count when the borrow is dropped
+    fn drop(&mut self) {
+        let count = unsafe {self.count_ptr.as_ref()};
+        count.set(count.get() - 1);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for LocalLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalLendCell")
+            .field("data", &self.as_ref())
+            .field("refcount", &self.borrow_count())
+            .finish()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for LocalBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalBorrowCell")
+            .field("data", &self.as_ref())
+            .finish()
+    }
+}
+
+impl<T: ?Sized> LendCell<T> for LocalLendCell<T> {
+    type Borrow = LocalBorrowCell<T>;
+
+    fn borrow(&self) -> LocalBorrowCell<T> {
+        LocalLendCell::borrow(self)
+    }
+
+    fn as_ref(&self) -> &T {
+        LocalLendCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for LocalBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        LocalBorrowCell::as_ref(self)
+    }
+}
+
+#[test]
+/// Tests that borrows read the value and are counted
+fn test_local_borrow() {
+    let x = LocalLendCell::new(4);
+    let xr = x.borrow();
+    assert_eq!(*xr, 4);
+    assert_eq!(x.borrow_count(), 1);
+    let xr2 = xr.clone();
+    assert_eq!(x.borrow_count(), 2);
+    drop(xr);
+    assert_eq!(*xr2, 4);
+    drop(xr2);
+    assert!(!x.is_borrowed());
+}
+
+#[test]
+/// Tests passing borrows down call frames
+fn test_local_nested_frames() {
+    fn depth(xr: LocalBorrowCell<Vec<i32>>, n: usize) -> usize {
+        if n == 0 { xr.len() } else { depth(xr.clone(), n - 1) + 1 }
+    }
+    let x = LocalLendCell::new(vec![1, 2, 3]);
+    assert_eq!(depth(x.borrow(), 3), 6);
+    assert_eq!(x.borrow_count(), 0);
+}
+
+#[test]
+/// Tests projecting a borrow onto a field
+fn test_local_map() {
+    let x = LocalLendCell::new((1, String::from("hello")));
+    let xr = x.borrow().map(|pair| &pair.1);
+    assert_eq!(x.borrow_count(), 1);
+    assert_eq!(xr.as_str(), "hello");
+}
+
+#[test]
+/// Tests that `get_mut` is refused while a borrow is alive
+fn test_local_get_mut() {
+    let mut x = LocalLendCell::new(4);
+    let xr = x.borrow();
+    drop(xr);
+    *x.get_mut().unwrap() += 1;
+    assert_eq!(x.into_inner(), 5);
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+#[should_panic(expected = "outlives")]
+/// Tests that dropping the cell with an outstanding borrow panics
+fn test_local_outlive() {
+    let x = LocalLendCell::new(4);
+    core::mem::forget(x.borrow());
+    drop(x);
+}
+
+#[test]
+#[cfg(not(feature = "abort-on-violation"))]
+#[should_panic(expected = "outlives")]
+/// Tests that `into_inner` refuses to move the value out while borrows are outstanding
+fn test_local_into_inner_borrowed() {
+    let x = LocalLendCell::new(4);
+    core::mem::forget(x.borrow());
+    x.into_inner();
+}
+
+#[test]
+/// Tests that a panicking projection releases the borrow it consumed
+fn test_local_map_panic_releases_borrow() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    let x = LocalLendCell::new((1, 2));
+    assert!(catch_unwind(AssertUnwindSafe(|| x.borrow().map(|_| -> &i32 { panic!("projection") }))).is_err());
+    assert_eq!(x.borrow_count(), 0);
+}
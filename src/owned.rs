@@ -0,0 +1,258 @@
+//! # Owned Lend Cells
+//!
+//! A reference-counting cell whose value lives on the heap and is freed by whichever of
+//! the owner and its borrows is dropped last, much like an `Arc` with a distinguished
+//! owner handle.
+//!
+//! Dropping the owner while borrows are outstanding does not panic. Instead the value is
+//! kept alive for the remaining borrows, which are marked as poisoned in the same spirit
+//! as a `Mutex` whose holder panicked: `try_as_ref` reports `BorrowError::Poisoned`, while
+//! `as_ref` and `Deref` keep reading the still valid value.
+
+use crate::{BorrowError, traits::BorrowHandle};
+use core::{fmt, ops::Deref, ptr::NonNull, sync::atomic::{AtomicBool, AtomicUsize, Ordering, fence}};
+use std::boxed::Box;
+
+/// The heap allocation shared between an owner and its borrows
+struct Shared<T: ?Sized> {
+    /// The number of handles, the owner included, that keep the allocation alive
+    holders: AtomicUsize,
+    poisoned: AtomicBool,
+    data: T
+}
+
+/// Drops one handle to the allocation, freeing it if it was the last one
+///
+/// # Safety
+///
+/// `shared` must point to a live allocation created by `OwnedLendCell::new`, and the
+/// calling handle must not use it afterwards.
+unsafe fn release<T: ?Sized>(shared: NonNull<Shared<T>>) {
+    // Publishes this handle's reads of the data to the handle that frees it
+    if unsafe { shared.as_ref() }.holders.fetch_sub(1, Ordering::Release) != 1 {
+        return;
+    }
+    // Synchronizes with the `Release` decrements of the other handles
+    fence(Ordering::Acquire);
+    drop(unsafe { Box::from_raw(shared.as_ptr()) });
+}
+
+/// The owner of a heap-allocated value that can be lent to other threads
+///
+/// Unlike `AtomicLendCell`, the owner may be dropped before its borrows, which poisons
+/// them instead of panicking.
+pub struct OwnedLendCell<T: ?Sized> {
+    shared: NonNull<Shared<T>>
+}
+
+/// A borrow of the value owned by an `OwnedLendCell`
+///
+/// The value stays alive for as long as the borrow exists, even after the owner has
+/// been dropped.
+pub struct OwnedBorrowCell<T: ?Sized> {
+    shared: NonNull<Shared<T>>
+}
+
+impl<T> OwnedLendCell<T> {
+    /// Creates a new `OwnedLendCell` moving the given value to the heap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::owned::OwnedLendCell;
+    ///
+    /// let cell = OwnedLendCell::new(42);
+    /// assert_eq!(*cell, 42);
+    /// ```
+    pub fn new(data: T) -> Self {
+        let shared = Box::new(Shared {holders: AtomicUsize::new(1), poisoned: AtomicBool::new(false), data});
+        Self {shared: NonNull::from(Box::leak(shared))}
+    }
+}
+
+impl<T: ?Sized> OwnedLendCell<T> {
+    fn shared(&self) -> &Shared<T> {
+        unsafe {self.shared.as_ref()}
+    }
+
+    /// Returns a reference to the contained value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        &self.shared().data
+    }
+
+    /// Creates a new `OwnedBorrowCell` for the contained value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::owned::OwnedLendCell;
+    ///
+    /// let cell = OwnedLendCell::new(String::from("hello"));
+    /// let borrow = cell.borrow();
+    /// let handle = std::thread::spawn(move || borrow.len());
+    ///
+    /// assert_eq!(handle.join().unwrap(), 5);
+    /// ```
+    pub fn borrow(&self) -> OwnedBorrowCell<T> {
+        // The owner keeps the allocation alive, so no synchronization is needed
+        self.shared().holders.fetch_add(1, Ordering::Relaxed);
+        OwnedBorrowCell {shared: self.shared}
+    }
+
+    /// Returns the number of outstanding borrows
+    pub fn borrow_count(&self) -> usize {
+        self.shared().holders.load(Ordering::Acquire) - 1
+    }
+}
+
+impl<T: ?Sized> Deref for OwnedLendCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedLendCell<T> {
+    /// Poisons any outstanding borrows and frees the value if there are none
+    fn drop(&mut self) {
+        self.shared().poisoned.store(true, Ordering::Release);
+        unsafe {release(self.shared)};
+    }
+}
+
+impl<T: ?Sized> OwnedBorrowCell<T> {
+    fn shared(&self) -> &Shared<T> {
+        unsafe {self.shared.as_ref()}
+    }
+
+    /// Returns a reference to the borrowed value
+    ///
+    /// The value is readable even if the owner has been dropped, so this never fails.
+    /// Use `try_as_ref` to find out about a dropped owner.
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &T {
+        &self.shared().data
+    }
+
+    /// Returns a reference to the borrowed value unless the borrow is poisoned
+    ///
+    /// # Errors
+    ///
+    /// Returns `BorrowError::Poisoned` if the owner has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomic_lend_cell::{BorrowError, owned::OwnedLendCell};
+    ///
+    /// let cell = OwnedLendCell::new(42);
+    /// let borrow = cell.borrow();
+    /// assert_eq!(borrow.try_as_ref(), Ok(&42));
+    ///
+    /// drop(cell);
+    /// assert_eq!(borrow.try_as_ref(), Err(BorrowError::Poisoned));
+    /// ```
+    pub fn try_as_ref(&self) -> Result<&T, BorrowError> {
+        if self.is_poisoned() {
+            Err(BorrowError::Poisoned)
+        } else {
+            Ok(self.as_ref())
+        }
+    }
+
+    /// Returns `true` if the owner has been dropped
+    pub fn is_poisoned(&self) -> bool {
+        self.shared().poisoned.load(Ordering::Acquire)
+    }
+}
+
+impl<T: ?Sized> Deref for OwnedBorrowCell<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized> Clone for OwnedBorrowCell<T> {
+    fn clone(&self) -> Self {
+        // The existing borrow keeps the allocation alive, so no synchronization is needed
+        self.shared().holders.fetch_add(1, Ordering::Relaxed);
+        OwnedBorrowCell {shared: self.shared}
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedBorrowCell<T> {
+    /// Frees the value if this is the last borrow of a dropped owner
+    fn drop(&mut self) {
+        unsafe {release(self.shared)};
+    }
+}
+
+impl<T: ?Sized> BorrowHandle<T> for OwnedBorrowCell<T> {
+    fn as_ref(&self) -> &T {
+        OwnedBorrowCell::as_ref(self)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for OwnedLendCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedLendCell")
+            .field("data", &self.as_ref())
+            .field("borrows", &self.borrow_count())
+            .finish()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for OwnedBorrowCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedBorrowCell")
+            .field("data", &self.as_ref())
+            .field("poisoned", &self.is_poisoned())
+            .finish()
+    }
+}
+
+// Whichever handle is dropped last drops the value, possibly on another thread, so
+// every handle requires `T: Send` in addition to `T: Sync`
+unsafe impl<T: ?Sized + Send + Sync> Send for OwnedLendCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedLendCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Send for OwnedBorrowCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedBorrowCell<T> {}
+
+#[test]
+/// Tests that borrows of a dropped owner are poisoned
+fn test_poisoned_read() {
+    let x = OwnedLendCell::new(String::from("kept"));
+    let xr = x.borrow();
+    let xr2 = xr.clone();
+    assert_eq!(x.borrow_count(), 2);
+    assert_eq!(xr.try_as_ref().map(String::as_str), Ok("kept"));
+
+    drop(x);
+    assert!(xr.is_poisoned());
+    assert_eq!(xr.try_as_ref(), Err(BorrowError::Poisoned));
+    assert_eq!(xr2.try_as_ref(), Err(BorrowError::Poisoned));
+    assert_eq!(*xr, "kept");
+}
+
+#[test]
+/// Tests that the value is dropped exactly once, by the last handle
+fn test_last_handle_drops() {
+    use std::sync::Arc;
+
+    let value = Arc::new(());
+    let x = OwnedLendCell::new(Arc::clone(&value));
+    let xr = x.borrow();
+    drop(x);
+    assert_eq!(Arc::strong_count(&value), 2);
+
+    let t = std::thread::spawn(move || assert!(xr.is_poisoned()));
+    t.join().unwrap();
+    assert_eq!(Arc::strong_count(&value), 1);
+
+    let x = OwnedLendCell::new(Arc::clone(&value));
+    drop(x.borrow());
+    drop(x);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
@@ -0,0 +1,46 @@
+//! # Cache Padding
+//!
+//! With the `padded` feature, the atomics shared between a cell and its borrows are
+//! placed on their own cache line, so that threads updating them do not invalidate the
+//! line holding the start of the data for threads reading it.
+
+use core::ops::{Deref, DerefMut};
+
+/// Aligns the wrapped value to a cache line when the `padded` feature is enabled
+///
+/// Since the alignment also rounds the size up, the field following it starts on a
+/// new cache line. Without the feature, it is a transparent wrapper.
+#[cfg_attr(feature = "padded", repr(align(64)))]
+#[cfg_attr(not(feature = "padded"), repr(transparent))]
+pub(crate) struct Padded<T>(T);
+
+impl<T> Padded<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Padded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Padded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[test]
+/// Tests that the wrapper only changes the layout with the `padded` feature
+fn test_padded_layout() {
+    use core::mem::{align_of, size_of};
+    if cfg!(feature = "padded") {
+        assert_eq!(align_of::<Padded<u8>>(), 64);
+        assert_eq!(size_of::<Padded<u8>>(), 64);
+    } else {
+        assert_eq!(size_of::<Padded<u8>>(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! # Scoped Borrows
+//!
+//! A borrow whose lifetime is tied to a closure, for lending data to scoped threads
+//! without erasing the lifetime of the borrow.
+//!
+//! `ScopedBorrow<'scope, T>` is handed out by `AtomicLendCell::with_scope` in both
+//! implementations. Since the borrow cannot escape the closure it was passed to, the
+//! compiler rather than a runtime check guarantees that it never outlives its owner.
+//!
+//! `BorrowGuard<'a, T>` goes the other way, turning an `AtomicBorrowCell` back into a
+//! lifetime-bound view that stays on the current thread.
+
+use core::{marker::PhantomData, ops::Deref};
+#[cfg(feature = "std")]
+use crate::traits::LendCell;
+#[cfg(feature = "std")]
+use std::thread::{Scope, ScopedJoinHandle};
+
+/// A lifetime-bound borrow of data contained in an `AtomicLendCell`
+///
+/// The `'scope` lifetime is invariant and only exists for the duration of the closure
+/// passed to `with_scope`, so the borrow cannot be returned from it or stored elsewhere:
+///
+/// ```compile_fail
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let escaped = cell.with_scope(|borrow| borrow);
+/// ```
+///
+/// It is `Send` and `Sync` whenever `T` is `Sync`, so it can be passed into scoped threads:
+///
+/// ```
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(vec![1, 2, 3]);
+/// let sum = cell.with_scope(|borrow| {
+///     std::thread::scope(|s| {
+///         let handle = s.spawn(move || borrow.iter().sum::<i32>());
+///         handle.join().unwrap()
+///     })
+/// });
+///
+/// assert_eq!(sum, 6);
+/// ```
+pub struct ScopedBorrow<'scope, T: ?Sized> {
+    data: &'scope T,
+    _invariant: PhantomData<fn(&'scope ()) -> &'scope ()>
+}
+
+impl<'scope, T: ?Sized> ScopedBorrow<'scope, T> {
+    pub(crate) fn new(data: &'scope T) -> Self {
+        Self { data, _invariant: PhantomData }
+    }
+
+    /// Returns a reference to the borrowed value
+    #[allow(clippy::should_implement_trait)]
+    pub fn as_ref(&self) -> &'scope T {
+        self.data
+    }
+}
+
+impl<T: ?Sized> Deref for ScopedBorrow<'_, T> {
+    type Target = T;
+    /// Dereferences to the borrowed value
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<T: ?Sized> Clone for ScopedBorrow<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ScopedBorrow<'_, T> {}
+
+/// A local view of an `AtomicBorrowCell` that is tied to the borrow's lifetime
+///
+/// Created by `AtomicBorrowCell::as_guard` in both implementations. While a guard is
+/// alive, the borrow it was created from can't be dropped, and unlike the borrow the
+/// guard is neither `Send` nor `Sync`, so it stays on the current thread:
+///
+/// ```compile_fail,E0277
+/// use atomic_lend_cell::AtomicLendCell;
+///
+/// let cell = AtomicLendCell::new(42);
+/// let borrow = cell.borrow();
+/// let guard = borrow.as_guard();
+/// std::thread::scope(|s| {
+///     s.spawn(move || *guard);
+/// });
+/// ```
+pub struct BorrowGuard<'a, T: ?Sized> {
+    data: &'a T,
+    _not_send: PhantomData<*const ()>
+}
+
+impl<'a, T: ?Sized> BorrowGuard<'a, T> {
+    pub(crate) fn new(data: &'a T) -> Self {
+        Self { data, _not_send: PhantomData }
+    }
+}
+
+impl<T: ?Sized> Deref for BorrowGuard<'_, T> {
+    type Target = T;
+    /// Dereferences to the borrowed value
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+/// A `std::thread::scope` whose threads can be handed borrows of a cell
+///
+/// Created by `AtomicLendCell::scope` in both implementations. All threads spawned
+/// in the scope are joined before `scope` returns, so their borrows are dropped
+/// before the cell can be.
+#[cfg(feature = "std")]
+pub struct LendScope<'scope, 'env: 'scope, C: ?Sized> {
+    scope: &'scope Scope<'scope, 'env>,
+    cell: &'env C
+}
+
+#[cfg(feature = "std")]
+impl<'scope, 'env, C: ?Sized> LendScope<'scope, 'env, C> {
+    pub(crate) fn new(scope: &'scope Scope<'scope, 'env>, cell: &'env C) -> Self {
+        Self { scope, cell }
+    }
+
+    /// Spawns a scoped thread that is passed a new borrow of the cell
+    pub fn spawn_with_borrow<T, F, R>(&self, f: F) -> ScopedJoinHandle<'scope, R>
+    where
+        T: ?Sized,
+        C: LendCell<T>,
+        C::Borrow: Send + 'scope,
+        F: FnOnce(C::Borrow) -> R + Send + 'scope,
+        R: Send + 'scope
+    {
+        let borrow = self.cell.borrow();
+        self.scope.spawn(move || f(borrow))
+    }
+
+    /// Returns the underlying `std::thread::Scope`, e.g. for spawning other threads
+    pub fn scope(&self) -> &'scope Scope<'scope, 'env> {
+        self.scope
+    }
+
+    /// Returns the cell whose value is lent in this scope
+    pub fn cell(&self) -> &'env C {
+        self.cell
+    }
+}
@@ -0,0 +1,67 @@
+//! # Backend Traits
+//!
+//! Traits implemented by the cells and borrows of both implementations, so that code
+//! can be generic over the lending strategy and leave the choice to the application.
+
+/// The owner side of a lending relationship
+///
+/// Implemented by `AtomicLendCell` in both implementations. The trait is object safe
+/// once the borrow type is fixed, e.g. `dyn LendCell<T, Borrow = AtomicBorrowCell<T>>`.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::traits::{BorrowHandle, LendCell};
+///
+/// fn sum<C: LendCell<Vec<i32>>>(cell: &C) -> i32 {
+///     let borrow = cell.borrow();
+///     borrow.as_ref().iter().sum()
+/// }
+///
+/// let flagged = atomic_lend_cell::flag_based::AtomicLendCell::new(vec![1, 2, 3]);
+/// let counted = atomic_lend_cell::atomic_counting::AtomicLendCell::new(vec![4, 5, 6]);
+///
+/// assert_eq!(sum(&flagged), 6);
+/// assert_eq!(sum(&counted), 15);
+/// ```
+pub trait LendCell<T: ?Sized> {
+    /// The borrow handed out by [`borrow`](Self::borrow)
+    type Borrow: BorrowHandle<T>;
+
+    /// Creates a new borrow of the contained value
+    fn borrow(&self) -> Self::Borrow;
+
+    /// Returns a reference to the contained value
+    fn as_ref(&self) -> &T;
+}
+
+/// The borrower side of a lending relationship
+///
+/// Implemented by `AtomicBorrowCell` in both implementations, as well as by the other
+/// borrows that can be held onto: `ReadView`, `ArcBorrow`, the counting
+/// `CompactBorrowCell` and `GuardedBorrow`, the flag-based `SharedFlagBorrowCell`,
+/// `LocalBorrowCell` and `OwnedBorrowCell`. Functions that only read the value can
+/// take any of them, which is the way to pass a borrow of one backend to an API
+/// written against the other. Converting the borrows themselves isn't possible: a
+/// flag-based cell doesn't count its borrows, so a counting borrow made from one would
+/// have nothing to keep its owner alive.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_lend_cell::traits::BorrowHandle;
+///
+/// fn total(borrow: impl BorrowHandle<Vec<i32>>) -> i32 {
+///     borrow.as_ref().iter().sum()
+/// }
+///
+/// let flagged = atomic_lend_cell::flag_based::AtomicLendCell::new(vec![1, 2, 3]);
+/// let counted = atomic_lend_cell::atomic_counting::AtomicLendCell::new(vec![4, 5, 6]);
+///
+/// assert_eq!(total(flagged.borrow()), 6);
+/// assert_eq!(total(counted.borrow().read_view()), 15);
+/// ```
+pub trait BorrowHandle<T: ?Sized> {
+    /// Returns a reference to the borrowed value
+    fn as_ref(&self) -> &T;
+}
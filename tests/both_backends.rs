@@ -0,0 +1,35 @@
+//! Both backends enabled at once, e.g. `cargo test --features ref-counting`
+#![cfg(all(feature = "flag-based", feature = "ref-counting"))]
+
+use atomic_lend_cell::{CountingBorrowCell, CountingLendCell, FlagBorrowCell, FlagLendCell, traits::BorrowHandle};
+
+#[test]
+/// Tests that each allocation can pick its own backend
+fn test_both_backends() {
+    let counted = CountingLendCell::new(1);
+    let flagged = FlagLendCell::new(2);
+
+    let counted_borrow: CountingBorrowCell<i32> = counted.borrow();
+    let flagged_borrow: FlagBorrowCell<i32> = flagged.borrow();
+
+    assert_eq!(*counted_borrow + *flagged_borrow, 3);
+    assert_eq!(counted.borrow_count(), 1);
+}
+
+#[test]
+/// Tests passing borrows of either backend to a generic consumer
+fn test_generic_borrow_consumer() {
+    fn shout<B: BorrowHandle<String>>(borrow: &B) -> String {
+        borrow.as_ref().to_uppercase()
+    }
+
+    let counted = CountingLendCell::new(String::from("counted"));
+    let flagged = FlagLendCell::new(String::from("flagged"));
+
+    let counted_borrow: CountingBorrowCell<String> = counted.borrow();
+    let flagged_borrow: FlagBorrowCell<String> = flagged.borrow();
+
+    assert_eq!(shout(&counted_borrow), "COUNTED");
+    assert_eq!(shout(&flagged_borrow), "FLAGGED");
+    assert_eq!(shout(&counted.borrow_compact()), "COUNTED");
+}
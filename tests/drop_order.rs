@@ -0,0 +1,156 @@
+//! Drop orders of owners and borrows, and whether each is reported as a violation
+//!
+//! Borrows that can't be released after the violation is reported are wrapped in
+//! `ManuallyDrop`, since their owner is gone by then. With `abort-on-violation`,
+//! reporting a violation ends the process, so none of this can be observed.
+#![cfg(not(feature = "abort-on-violation"))]
+
+use atomic_lend_cell::atomic_counting;
+use std::{any::Any, mem::ManuallyDrop, panic::{self, AssertUnwindSafe}};
+
+/// Returns `true` if the panic payload reports a borrow outliving its owner, in either
+/// implementation
+fn is_outlive_panic(payload: &(dyn Any + Send)) -> bool {
+    let message = payload.downcast_ref::<String>().map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or_default();
+    message.contains("outlives the AtomicLendCell") || message.contains("after its owner was dropped")
+}
+
+/// Runs `f` and returns its result, failing if a borrow outlived its owner
+///
+/// Panics for any other reason are passed on unchanged.
+fn assert_no_outlive<R>(f: impl FnOnce() -> R) -> R {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) if is_outlive_panic(&*payload) => panic!("a borrow outlived its owner"),
+        Err(payload) => panic::resume_unwind(payload)
+    }
+}
+
+/// Runs `f`, failing unless a borrow outlives its owner
+///
+/// Panics for any other reason are passed on unchanged.
+fn assert_outlives(f: impl FnOnce()) {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(()) => panic!("no borrow outlived its owner"),
+        Err(payload) if is_outlive_panic(&*payload) => {}
+        Err(payload) => panic::resume_unwind(payload)
+    }
+}
+
+#[test]
+/// Tests a borrow declared in an outer scope of an owner in an inner scope
+fn test_borrow_in_outer_scope() {
+    assert_outlives(|| {
+        let borrow;
+        {
+            let cell = atomic_counting::AtomicLendCell::new(1);
+            borrow = ManuallyDrop::new(cell.borrow());
+        }
+        let _ = borrow;
+    });
+    assert_no_outlive(|| {
+        let cell = atomic_counting::AtomicLendCell::new(1);
+        {
+            let borrow = cell.borrow();
+            assert_eq!(*borrow, 1);
+        }
+    });
+}
+
+#[test]
+#[cfg(debug_assertions)]
+/// Tests that the flag-based borrow reports being dropped after its owner in an inner scope
+fn test_flag_borrow_in_outer_scope() {
+    assert_outlives(|| {
+        let borrow;
+        {
+            let cell = atomic_lend_cell::flag_based::AtomicLendCell::new(1);
+            borrow = cell.borrow();
+        }
+        drop(borrow);
+    });
+}
+
+#[test]
+#[cfg(not(feature = "no-send"))]
+/// Tests borrows moved into threads that are joined before or after the owner is dropped
+fn test_borrow_moved_into_thread() {
+    assert_no_outlive(|| {
+        let cell = atomic_counting::AtomicLendCell::new(vec![1, 2, 3]);
+        let borrow = cell.borrow();
+        let sum = std::thread::spawn(move || borrow.iter().sum::<i32>()).join().unwrap();
+        assert_eq!(sum, 6);
+    });
+
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    let mut worker = None;
+    assert_outlives(|| {
+        let cell = atomic_counting::AtomicLendCell::new(vec![1, 2, 3]);
+        let borrow = ManuallyDrop::new(cell.borrow());
+        // The thread keeps the borrow until after the owner's drop has been reported
+        worker = Some(std::thread::spawn(move || {
+            let _borrow = borrow;
+            released.recv().unwrap();
+        }));
+    });
+    release.send(()).unwrap();
+    worker.unwrap().join().unwrap();
+}
+
+#[test]
+/// Tests that locals are dropped in reverse order of declaration, so a collection of
+/// borrows has to be declared after the owner
+fn test_declaration_order() {
+    assert_no_outlive(|| {
+        let cell = atomic_counting::AtomicLendCell::new(1);
+        let borrows: Vec<_> = (0..4).map(|_| cell.borrow()).collect();
+        assert_eq!(cell.borrow_count(), borrows.len());
+    });
+    assert_outlives(|| {
+        let mut borrows = Vec::new();
+        let cell = atomic_counting::AtomicLendCell::new(1);
+        borrows.extend((0..4).map(|_| ManuallyDrop::new(cell.borrow())));
+    });
+}
+
+#[test]
+/// Tests that struct fields are dropped in declaration order, so a borrow has to be
+/// declared before the boxed owner it borrows from
+fn test_field_order() {
+    struct BorrowFirst {
+        _borrow: atomic_counting::AtomicBorrowCell<i32>,
+        _cell: Box<atomic_counting::AtomicLendCell<i32>>
+    }
+
+    struct OwnerFirst {
+        _cell: Box<atomic_counting::AtomicLendCell<i32>>,
+        _borrow: ManuallyDrop<atomic_counting::AtomicBorrowCell<i32>>
+    }
+
+    assert_no_outlive(|| {
+        let cell = Box::new(atomic_counting::AtomicLendCell::new(1));
+        drop(BorrowFirst {_borrow: cell.borrow(), _cell: cell});
+    });
+    assert_outlives(|| {
+        let cell = Box::new(atomic_counting::AtomicLendCell::new(1));
+        drop(OwnerFirst {_borrow: ManuallyDrop::new(cell.borrow()), _cell: cell});
+    });
+}
+
+#[test]
+#[cfg(all(feature = "std", not(feature = "no-send")))]
+/// Tests that a blocking owner waits for a borrow held by an unjoined thread instead of
+/// reporting it
+fn test_blocking_owner_waits_for_thread() {
+    let worker = assert_no_outlive(|| {
+        let cell = atomic_counting::AtomicLendCell::new_blocking(1);
+        let borrow = cell.borrow();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            *borrow
+        })
+    });
+    assert_eq!(worker.join().unwrap(), 1);
+}